@@ -0,0 +1,47 @@
+use std::io::{self, Write};
+
+use aoc2024::gen::{self, Rng};
+
+const USAGE: &str = "usage: aoc gen <day> [--seed <n>] [--size <n>]";
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(|s| s.as_str()) {
+        Some("gen") => generate(&args[1..]),
+        _ => panic!("{USAGE}"),
+    }
+}
+
+// `aoc gen <day>` prints a random input for the day, the same one every time for a seed
+fn generate(args: &[String]) {
+    let day: u32 = args
+        .first()
+        .unwrap_or_else(|| panic!("{USAGE}"))
+        .parse()
+        .expect("invalid day");
+    let value_of = |flag: &str| {
+        args.iter().position(|a| a == flag).map(|i| {
+            args.get(i + 1)
+                .unwrap_or_else(|| panic!("{flag} needs a value"))
+                .parse()
+                .unwrap_or_else(|_| panic!("invalid value for {flag}"))
+        })
+    };
+    let seed = value_of("--seed").unwrap_or(1);
+    let size = value_of("--size").map_or(gen::default_size(day), |size| size as usize);
+    let mut rng = Rng::new(seed);
+
+    let input = if day == 13 {
+        // the answers are known for claw machines, so they can be checked against
+        let (input, [part1, part2]) = gen::claw_machines(&mut rng, size);
+        eprintln!("part 1: fewest tokens: {part1}");
+        eprintln!("part 2: fewest tokens: {part2}");
+        input
+    } else {
+        gen::generate(day, &mut rng, size)
+            .unwrap_or_else(|| panic!("there's no generator for day {day}"))
+    };
+    io::stdout()
+        .write_all(input.as_bytes())
+        .expect("couldn't write input");
+}
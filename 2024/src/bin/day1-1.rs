@@ -1,34 +1,31 @@
-use std::fs::File;
-use std::io::{self, BufRead};
+use aoc2024::day1::{median, mode, read_lists};
 
-fn main() -> io::Result<()> {
+fn main() {
     let path = "inputs/day1";
-    let mut left_list = Vec::new();
-    let mut right_list = Vec::new();
-    let mut distance_sum = 0;
-
-    let file = File::open(path)?;
-    let reader = io::BufReader::new(file);
-
-    for line in reader.lines() {
-        let line = line?;
-        let values: Vec<u64> = line
-            .split_whitespace()
-            .filter_map(|s| s.parse::<u64>().ok())
-            .collect();
-        left_list.push(values[0]);
-        right_list.push(values[1]);
+    let (lists, errors) = read_lists(path);
+    for error in &errors {
+        println!("skipped {error}");
     }
 
-    left_list.sort();
-    right_list.sort();
-
-    for i in 0..left_list.len() {
-        let dist = (left_list[i] as i64 - right_list[i] as i64).unsigned_abs();
-        distance_sum += dist;
+    // `--stats` also shows the median and mode of each list and of the distances, and how
+    // many pairs are each distance apart
+    if std::env::args().any(|a| a == "--stats") {
+        let differences = lists.differences();
+        for (name, values) in [
+            ("left list", &lists.left),
+            ("right list", &lists.right),
+            ("distances", &differences),
+        ] {
+            println!(
+                "{name}: median {:?}, mode {:?}",
+                median(values),
+                mode(values)
+            );
+        }
+        for (difference, count) in lists.difference_histogram() {
+            println!("distance {difference}: {count} pairs");
+        }
     }
 
-    println!("sum of distances: {distance_sum}");
-
-    Ok(())
+    println!("sum of distances: {}", lists.total_distance());
 }
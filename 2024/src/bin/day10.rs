@@ -4,18 +4,18 @@ use std::{
     io::{BufRead, BufReader},
 };
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 struct Point {
     x: i32,
     y: i32,
 }
 
 impl Point {
-    fn is_in_map(&self, map: &Vec<Vec<u8>>) -> bool {
+    fn is_in_map(&self, map: &[Vec<u8>]) -> bool {
         self.x >= 0 && self.x < map[0].len() as i32 && self.y >= 0 && self.y < map.len() as i32
     }
 
-    fn value(&self, map: &Vec<Vec<u8>>) -> Option<u8> {
+    fn value(&self, map: &[Vec<u8>]) -> Option<u8> {
         if self.is_in_map(map) {
             Some(map[self.y as usize][self.x as usize])
         } else {
@@ -23,7 +23,9 @@ impl Point {
         }
     }
 
-    fn viable_neighbours(&self, map: &Vec<Vec<u8>>) -> Vec<Self> {
+    // neighbours exactly `step` higher than this point
+    fn viable_neighbours(&self, map: &[Vec<u8>], step: i32) -> Vec<Self> {
+        let height = self.value(map).unwrap() as i32;
         [
             Point {
                 x: self.x + 1,
@@ -43,29 +45,203 @@ impl Point {
             },
         ]
         .into_iter()
-        .filter(|p| {
-            p.value(map)
-                .is_some_and(|v| v == self.value(map).unwrap() + 1)
-        })
+        .filter(|p| p.value(map).is_some_and(|v| v as i32 == height + step))
         .collect()
     }
 }
 
+// what counts as a trail: it starts at height `start`, ends at height `end`, and every step
+// along it changes the height by exactly `step`
+#[derive(Clone, Copy, Debug)]
+struct TrailRules {
+    start: u8,
+    end: u8,
+    step: i32,
+}
+
+impl Default for TrailRules {
+    fn default() -> Self {
+        Self {
+            start: 0,
+            end: 9,
+            step: 1,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct TrailheadReport {
+    head: Point,
+    summits: Vec<Point>,
+    paths: u64,
+}
+
+struct TrailAnalyser<'a> {
+    map: &'a [Vec<u8>],
+    rules: TrailRules,
+    // number of distinct trails from each point to a summit
+    ratings: Vec<Vec<Option<u64>>>,
+}
+
+impl<'a> TrailAnalyser<'a> {
+    fn new(map: &'a [Vec<u8>], rules: TrailRules) -> Self {
+        Self {
+            map,
+            rules,
+            ratings: vec![vec![None; map[0].len()]; map.len()],
+        }
+    }
+
+    fn trailheads(&self) -> Vec<Point> {
+        let mut heads = Vec::new();
+        for (y, row) in self.map.iter().enumerate() {
+            for (x, height) in row.iter().enumerate() {
+                if *height == self.rules.start {
+                    heads.push(Point {
+                        x: x as i32,
+                        y: y as i32,
+                    });
+                }
+            }
+        }
+        heads
+    }
+
+    fn is_summit(&self, point: &Point) -> bool {
+        point.value(self.map) == Some(self.rules.end)
+    }
+
+    // the summits reachable from `head`, in order
+    fn summits(&self, head: Point) -> Vec<Point> {
+        let mut visited = HashSet::from([head]);
+        let mut stack = vec![head];
+        let mut summits = Vec::new();
+        while let Some(point) = stack.pop() {
+            if self.is_summit(&point) {
+                summits.push(point);
+                continue;
+            }
+            for n in point.viable_neighbours(self.map, self.rules.step) {
+                if visited.insert(n) {
+                    stack.push(n);
+                }
+            }
+        }
+        summits.sort();
+        summits
+    }
+
+    fn rating(&mut self, point: Point) -> u64 {
+        if let Some(rating) = self.ratings[point.y as usize][point.x as usize] {
+            return rating;
+        }
+        let rating = if self.is_summit(&point) {
+            1
+        } else {
+            point
+                .viable_neighbours(self.map, self.rules.step)
+                .into_iter()
+                .map(|n| self.rating(n))
+                .sum()
+        };
+        self.ratings[point.y as usize][point.x as usize] = Some(rating);
+        rating
+    }
+
+    // up to `limit` distinct trails from `head` to a summit
+    fn paths(&self, head: Point, limit: usize) -> Vec<Vec<Point>> {
+        let mut paths = Vec::new();
+        let mut stack = vec![vec![head]];
+        while let Some(path) = stack.pop() {
+            if paths.len() >= limit {
+                break;
+            }
+            let last = path[path.len() - 1];
+            if self.is_summit(&last) {
+                paths.push(path);
+                continue;
+            }
+            for n in last.viable_neighbours(self.map, self.rules.step) {
+                let mut next = path.clone();
+                next.push(n);
+                stack.push(next);
+            }
+        }
+        paths
+    }
+
+    fn analyse(&mut self) -> Vec<TrailheadReport> {
+        self.trailheads()
+            .into_iter()
+            .map(|head| TrailheadReport {
+                head,
+                summits: self.summits(head),
+                paths: self.rating(head),
+            })
+            .collect()
+    }
+}
+
 fn main() {
     let path = "inputs/day10";
     let topological_map = parse_map(path);
 
+    // `--start <h> --end <h> --step <n>` change what counts as a trail, `--report` lists every
+    // trailhead and `--paths <n>` lists up to n trails from each trailhead
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let rules = rules_from_args(&args);
+
+    if args.iter().any(|a| a == "--report" || a == "--paths") {
+        let limit = args
+            .iter()
+            .position(|a| a == "--paths")
+            .map(|i| {
+                args.get(i + 1)
+                    .and_then(|n| n.parse().ok())
+                    .expect("--paths needs a number")
+            })
+            .unwrap_or(0);
+        report(&topological_map, rules, limit);
+        return;
+    }
+
     println!(
         "part 1: trailheads score: {:?}",
-        solve_part1(&topological_map)
+        solve_part1(&topological_map, rules)
     );
 
     println!(
         "part 2: trailheads ratings: {:?}",
-        solve_part2(&topological_map)
+        solve_part2(&topological_map, rules)
     );
 }
 
+fn rules_from_args(args: &[String]) -> TrailRules {
+    let mut rules = TrailRules::default();
+    let value_of = |flag: &str| {
+        args.iter().position(|a| a == flag).map(|i| {
+            args.get(i + 1)
+                .unwrap_or_else(|| panic!("{flag} needs a value"))
+                .as_str()
+        })
+    };
+    if let Some(start) = value_of("--start") {
+        rules.start = start.parse().expect("invalid start height");
+    }
+    if let Some(end) = value_of("--end") {
+        rules.end = end.parse().expect("invalid end height");
+    }
+    if let Some(step) = value_of("--step") {
+        // a step of 0 would let trails go round in circles forever
+        rules.step = step
+            .parse()
+            .ok()
+            .filter(|step| *step != 0)
+            .expect("invalid step");
+    }
+    rules
+}
+
 fn parse_map(path: &str) -> Vec<Vec<u8>> {
     let file = File::open(path).expect("couldn't open file");
     let reader = BufReader::new(file);
@@ -81,107 +257,116 @@ fn parse_map(path: &str) -> Vec<Vec<u8>> {
         .collect()
 }
 
-fn find_trailhead_score(map: &Vec<Vec<u8>>, head: Point, visited: &mut HashSet<Point>) -> u32 {
-    visited.insert(head);
-
-    if head.value(map).is_some_and(|v| v == 9) {
-        return 1;
+fn report(map: &[Vec<u8>], rules: TrailRules, limit: usize) {
+    let mut analyser = TrailAnalyser::new(map, rules);
+    for trailhead in analyser.analyse() {
+        let Point { x, y } = trailhead.head;
+        println!(
+            "trailhead ({x}, {y}): {} summits, {} trails",
+            trailhead.summits.len(),
+            trailhead.paths
+        );
+        for path in analyser.paths(trailhead.head, limit) {
+            let points = path
+                .iter()
+                .map(|p| format!("({}, {})", p.x, p.y))
+                .collect::<Vec<_>>();
+            println!("  {}", points.join(" -> "));
+        }
     }
+}
 
-    let viable_neighbours = head
-        .viable_neighbours(map)
+fn solve_part1(map: &[Vec<u8>], rules: TrailRules) -> u64 {
+    let analyser = TrailAnalyser::new(map, rules);
+    analyser
+        .trailheads()
         .into_iter()
-        .filter(|n| !visited.contains(n))
-        .collect::<Vec<_>>();
-
-    if viable_neighbours.len() == 0 {
-        return 0;
-    }
+        .map(|head| analyser.summits(head).len() as u64)
+        .sum()
+}
 
-    let score = viable_neighbours
+fn solve_part2(map: &[Vec<u8>], rules: TrailRules) -> u64 {
+    let mut analyser = TrailAnalyser::new(map, rules);
+    analyser
+        .trailheads()
         .into_iter()
-        .map(|n| find_trailhead_score(map, n, visited))
-        .sum();
-
-    score
+        .map(|head| analyser.rating(head))
+        .sum()
 }
 
-fn solve_part1(map: &Vec<Vec<u8>>) -> u32 {
-    let map_width = map[0].len();
-    let map_height = map.len();
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let mut scores_sum = 0;
+    const EXAMPLE: &str = "\
+89010123
+78121874
+87430965
+96549874
+45678903
+32019012
+01329801
+10456732";
 
-    for y in 0..map_height {
-        for x in 0..map_width {
-            if map[y][x] == 0 {
-                let mut visited: HashSet<Point> = HashSet::new();
-                let score = find_trailhead_score(
-                    map,
-                    Point {
-                        x: x as i32,
-                        y: y as i32,
-                    },
-                    &mut visited,
-                );
-                scores_sum += score;
-            }
-        }
-    }
-    scores_sum
-}
-
-fn find_trailhead_rating(
-    map: &Vec<Vec<u8>>,
-    head: Point,
-    score_cache: &mut Vec<Vec<Option<u32>>>,
-) -> u32 {
-    if let Some(score) = score_cache[head.y as usize][head.x as usize] {
-        return score;
+    fn parse(text: &str) -> Vec<Vec<u8>> {
+        text.lines()
+            .map(|line| line.bytes().map(|b| b - b'0').collect())
+            .collect()
     }
 
-    if head.value(map).is_some_and(|v| v == 9) {
-        return 1;
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
     }
 
-    let viable_neighbours = head.viable_neighbours(map).into_iter().collect::<Vec<_>>();
+    #[test]
+    fn example() {
+        let map = parse(EXAMPLE);
+        assert_eq!(solve_part1(&map, TrailRules::default()), 36);
+        assert_eq!(solve_part2(&map, TrailRules::default()), 81);
 
-    if viable_neighbours.len() == 0 {
-        return 0;
+        // walking every trail backwards from its summit finds the same trails
+        let reversed = rules_from_args(&args(&["--start", "9", "--end", "0", "--step", "-1"]));
+        assert_eq!(solve_part2(&map, reversed), 81);
     }
 
-    let score = viable_neighbours
-        .into_iter()
-        .map(|n| find_trailhead_rating(map, n, score_cache))
-        .sum();
-
-    score_cache[head.y as usize][head.x as usize] = Some(score);
+    #[test]
+    fn summits_and_ratings() {
+        let map = parse(EXAMPLE);
+        let mut analyser = TrailAnalyser::new(&map, TrailRules::default());
+        let reports = analyser.analyse();
+        let scores = reports.iter().map(|r| r.summits.len()).collect::<Vec<_>>();
+        let ratings = reports.iter().map(|r| r.paths).collect::<Vec<_>>();
+        assert_eq!(scores, [5, 6, 5, 3, 1, 3, 5, 3, 5]);
+        assert_eq!(ratings, [20, 24, 10, 4, 1, 4, 5, 8, 5]);
 
-    score
-}
-
-fn solve_part2(map: &Vec<Vec<u8>>) -> u32 {
-    let map_width = map[0].len();
-    let map_height = map.len();
-
-    let mut ratings_sum = 0;
+        let small = parse("0123\n1234\n8765\n9876");
+        let analyser = TrailAnalyser::new(&small, TrailRules::default());
+        assert_eq!(
+            analyser.summits(Point { x: 0, y: 0 }),
+            [Point { x: 0, y: 3 }]
+        );
+    }
 
-    let mut score_cache: Vec<Vec<Option<u32>>> = vec![vec![None; map_width]; map_height];
+    #[test]
+    fn capped_paths() {
+        let map = parse(EXAMPLE);
+        let analyser = TrailAnalyser::new(&map, TrailRules::default());
+        let head = Point { x: 2, y: 0 };
+        assert_eq!(analyser.paths(head, 3).len(), 3);
 
-    for y in 0..map_height {
-        for x in 0..map_width {
-            if map[y][x] == 0 {
-                let score = find_trailhead_rating(
-                    map,
-                    Point {
-                        x: x as i32,
-                        y: y as i32,
-                    },
-                    &mut score_cache,
-                );
-                ratings_sum += score;
-            }
+        let paths = analyser.paths(head, 100);
+        assert_eq!(paths.len(), 20);
+        assert_eq!(paths.iter().collect::<HashSet<_>>().len(), 20);
+        for path in &paths {
+            let heights = path.iter().map(|p| p.value(&map).unwrap());
+            assert_eq!(path[0], head);
+            assert!(heights.eq(0..=9));
         }
     }
-    ratings_sum
+
+    #[test]
+    #[should_panic(expected = "invalid step")]
+    fn step_zero_is_rejected() {
+        rules_from_args(&args(&["--step", "0"]));
+    }
 }
@@ -1,9 +1,25 @@
 use std::{collections::HashMap, fs::File, io::Read};
 
+use num_bigint::BigUint;
+
 fn main() {
     let path = "inputs/day11";
     let stones = parse_input(path);
 
+    // `--blinks <n>` reports on the stones after any number of blinks, with `--distinct`
+    // listing how many different stones there are in each generation and `--histogram`
+    // listing how many there are of each stone at the end
+    // `--multiplier <n>` and `--split-parts <n>` swap in a variant of the default rules
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(i) = args.iter().position(|a| a == "--blinks") {
+        let blinks = args
+            .get(i + 1)
+            .and_then(|b| b.parse().ok())
+            .expect("--blinks needs a number");
+        report(&stones, blinks, &args);
+        return;
+    }
+
     println!(
         "part 1: stones after blinking 25 times: {:?}",
         solve_part1(&stones)
@@ -15,7 +31,11 @@ fn main() {
     );
 }
 
-type Stone = u64;
+// engraved numbers can grow past u64 with some `--multiplier`s
+type Stone = BigUint;
+
+// number of stones of each kind
+type Histogram = HashMap<Stone, BigUint>;
 
 fn parse_input(path: &str) -> HashMap<Stone, u64> {
     let mut file = File::open(path).expect("couldn't open input file");
@@ -25,65 +45,361 @@ fn parse_input(path: &str) -> HashMap<Stone, u64> {
     let mut stones = HashMap::new();
 
     buf.split_whitespace()
-        .filter_map(|x| x.parse::<u64>().ok())
+        .filter_map(|x| x.parse::<Stone>().ok())
         .for_each(|s| {
             stones.entry(s).and_modify(|count| *count += 1).or_insert(1);
         });
     stones
 }
 
-fn digits(x: u64) -> u64 {
-    ((x as f64).log10() + 1.0).floor() as u64
+fn digits(x: &Stone) -> u64 {
+    x.to_string().len() as u64
+}
+
+fn split(x: &Stone) -> (Stone, Stone) {
+    let divisor = Stone::from(10_u32).pow((digits(x) / 2) as u32);
+    (x / &divisor, x % divisor)
+}
+
+// one way a stone can change when blinking
+trait Rule {
+    fn applies(&self, stone: &Stone) -> bool;
+    fn apply(&self, stone: &Stone) -> Vec<Stone>;
+}
+
+// a stone engraved with `from` is replaced by one engraved with `to`
+struct Replace {
+    from: Stone,
+    to: Stone,
+}
+
+impl Rule for Replace {
+    fn applies(&self, stone: &Stone) -> bool {
+        *stone == self.from
+    }
+
+    fn apply(&self, _stone: &Stone) -> Vec<Stone> {
+        vec![self.to.clone()]
+    }
 }
 
-fn split(x: u64) -> (u64, u64) {
-    let exp = (digits(x) / 2) as u32;
-    let divisor = 10_u64.pow(exp);
-    (x / divisor, x % divisor)
+// a stone whose digits can be split evenly into `parts` is split into that many stones
+struct SplitDigits {
+    parts: u64,
 }
 
-fn blink(stones: HashMap<Stone, u64>) -> HashMap<Stone, u64> {
-    let mut new_stones = HashMap::new();
+impl Rule for SplitDigits {
+    fn applies(&self, stone: &Stone) -> bool {
+        *stone > Stone::ZERO && digits(stone).is_multiple_of(self.parts)
+    }
 
-    for (stone, count) in stones.into_iter() {
-        let stone_digits = digits(stone);
-        if stone == 0 {
-            new_stones
-                .entry(1)
-                .and_modify(|c| *c += count)
-                .or_insert(count);
-        } else if stone_digits % 2 == 0 {
-            let split = split(stone);
-            new_stones
-                .entry(split.0)
-                .and_modify(|c| *c += count)
-                .or_insert(count);
-            new_stones
-                .entry(split.1)
-                .and_modify(|c| *c += count)
-                .or_insert(count);
-        } else {
-            new_stones
-                .entry(stone * 2024)
-                .and_modify(|c| *c += count)
-                .or_insert(count);
+    fn apply(&self, stone: &Stone) -> Vec<Stone> {
+        if self.parts == 2 {
+            let (left, right) = split(stone);
+            return vec![left, right];
+        }
+        let divisor = Stone::from(10_u32).pow((digits(stone) / self.parts) as u32);
+        let mut parts = Vec::new();
+        let mut rest = stone.clone();
+        for _ in 0..self.parts {
+            parts.push(&rest % &divisor);
+            rest /= &divisor;
         }
+        parts.reverse();
+        parts
+    }
+}
+
+// any stone is replaced by one engraved with its number times `factor`
+struct Multiply {
+    factor: u64,
+}
+
+impl Rule for Multiply {
+    fn applies(&self, _stone: &Stone) -> bool {
+        true
+    }
+
+    fn apply(&self, stone: &Stone) -> Vec<Stone> {
+        vec![stone * self.factor]
     }
-    new_stones
 }
 
-fn solve_part1(stones: &HashMap<Stone, u64>) -> u64 {
-    let mut stones = stones.clone();
-    for _ in 1..=25 {
-        stones = blink(stones);
+fn default_rules() -> Vec<Box<dyn Rule>> {
+    vec![
+        Box::new(Replace {
+            from: Stone::from(0_u32),
+            to: Stone::from(1_u32),
+        }),
+        Box::new(SplitDigits { parts: 2 }),
+        Box::new(Multiply { factor: 2024 }),
+    ]
+}
+
+struct StoneEngine {
+    // tried in order, the first rule that applies to a stone decides what it turns into
+    rules: Vec<Box<dyn Rule>>,
+    // number of stones a stone turns into after some number of blinks
+    counts: HashMap<(Stone, u32), BigUint>,
+}
+
+impl Default for StoneEngine {
+    fn default() -> Self {
+        Self::new(default_rules())
     }
-    stones.into_values().sum()
 }
 
-fn solve_part2(stones: &HashMap<Stone, u64>) -> u64 {
-    let mut stones = stones.clone();
-    for _ in 1..=75 {
-        stones = blink(stones);
+impl StoneEngine {
+    fn new(rules: Vec<Box<dyn Rule>>) -> Self {
+        Self {
+            rules,
+            counts: HashMap::new(),
+        }
+    }
+
+    // the stones a single stone turns into when blinking once. Stones no rule applies to
+    // stay as they are.
+    fn transform(&self, stone: &Stone) -> Vec<Stone> {
+        match self.rules.iter().find(|rule| rule.applies(stone)) {
+            Some(rule) => rule.apply(stone),
+            None => vec![stone.clone()],
+        }
+    }
+
+    fn count(&mut self, stone: &Stone, blinks: u32) -> BigUint {
+        if blinks == 0 {
+            return BigUint::from(1_u32);
+        }
+        let key = (stone.clone(), blinks);
+        if let Some(count) = self.counts.get(&key) {
+            return count.clone();
+        }
+        let count = self
+            .transform(stone)
+            .iter()
+            .map(|s| self.count(s, blinks - 1))
+            .sum::<BigUint>();
+        self.counts.insert(key, count.clone());
+        count
+    }
+
+    fn count_all(&mut self, stones: &HashMap<Stone, u64>, blinks: u32) -> BigUint {
+        stones
+            .iter()
+            .map(|(stone, n)| self.count(stone, blinks) * *n)
+            .sum()
+    }
+
+    fn blink(&self, stones: &Histogram, new_stones: &mut Histogram) {
+        new_stones.clear();
+        for (stone, count) in stones.iter() {
+            for new_stone in self.transform(stone) {
+                *new_stones.entry(new_stone).or_default() += count;
+            }
+        }
+    }
+
+    // number of different stones after each blink, starting with the stones before the first
+    fn distinct_per_generation(&self, stones: &HashMap<Stone, u64>, blinks: u32) -> Vec<usize> {
+        let mut distinct = vec![stones.len()];
+        let mut current = to_histogram(stones);
+        let mut next = Histogram::new();
+        for _ in 0..blinks {
+            self.blink(&current, &mut next);
+            std::mem::swap(&mut current, &mut next);
+            distinct.push(current.len());
+        }
+        distinct
+    }
+
+    fn histogram(&self, stones: &HashMap<Stone, u64>, blinks: u32) -> Histogram {
+        let mut current = to_histogram(stones);
+        let mut next = Histogram::new();
+        for _ in 0..blinks {
+            self.blink(&current, &mut next);
+            std::mem::swap(&mut current, &mut next);
+        }
+        current
+    }
+}
+
+fn to_histogram(stones: &HashMap<Stone, u64>) -> Histogram {
+    stones
+        .iter()
+        .map(|(stone, count)| (stone.clone(), BigUint::from(*count)))
+        .collect()
+}
+
+fn rules_from_args(args: &[String]) -> Vec<Box<dyn Rule>> {
+    let value_of = |flag: &str, default: u64| match args.iter().position(|a| a == flag) {
+        Some(i) => args
+            .get(i + 1)
+            .and_then(|v| v.parse().ok())
+            .unwrap_or_else(|| panic!("{flag} needs a number")),
+        None => default,
+    };
+    vec![
+        Box::new(Replace {
+            from: Stone::from(0_u32),
+            to: Stone::from(1_u32),
+        }),
+        Box::new(SplitDigits {
+            parts: value_of("--split-parts", 2),
+        }),
+        Box::new(Multiply {
+            factor: value_of("--multiplier", 2024),
+        }),
+    ]
+}
+
+fn report(stones: &HashMap<Stone, u64>, blinks: u32, args: &[String]) {
+    let engine = StoneEngine::new(rules_from_args(args));
+
+    if args.iter().any(|a| a == "--distinct") {
+        for (i, distinct) in engine
+            .distinct_per_generation(stones, blinks)
+            .iter()
+            .enumerate()
+        {
+            println!("blink {i}: {distinct} distinct stones");
+        }
+    }
+
+    let histogram = engine.histogram(stones, blinks);
+    if args.iter().any(|a| a == "--histogram") {
+        let mut entries = histogram.iter().collect::<Vec<_>>();
+        entries.sort();
+        for (stone, count) in entries {
+            println!("{stone}: {count}");
+        }
+    }
+
+    println!(
+        "stones after blinking {blinks} times: {}",
+        histogram.values().sum::<BigUint>()
+    );
+}
+
+fn solve_part1(stones: &HashMap<Stone, u64>) -> BigUint {
+    StoneEngine::default().count_all(stones, 25)
+}
+
+fn solve_part2(stones: &HashMap<Stone, u64>) -> BigUint {
+    StoneEngine::default().count_all(stones, 75)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn list(values: &[u64]) -> Vec<Stone> {
+        values.iter().map(|v| Stone::from(*v)).collect()
+    }
+
+    fn stones(values: &[u64]) -> HashMap<Stone, u64> {
+        let mut stones = HashMap::new();
+        for stone in list(values) {
+            *stones.entry(stone).or_default() += 1;
+        }
+        stones
+    }
+
+    fn transform(engine: &StoneEngine, stone: u64) -> Vec<Stone> {
+        engine.transform(&Stone::from(stone))
+    }
+
+    // blinks one stone at a time, keeping every stone in a list
+    fn naive_count(engine: &StoneEngine, values: &[u64], blinks: u32) -> u64 {
+        let mut stones = list(values);
+        for _ in 0..blinks {
+            stones = stones.iter().flat_map(|s| engine.transform(s)).collect();
+        }
+        stones.len() as u64
+    }
+
+    #[test]
+    fn example() {
+        let mut engine = StoneEngine::default();
+        let example = stones(&[125, 17]);
+        assert_eq!(engine.count_all(&example, 6), BigUint::from(22_u32));
+        assert_eq!(engine.count_all(&example, 25), BigUint::from(55312_u32));
+    }
+
+    #[test]
+    fn default_rules_transform() {
+        let engine = StoneEngine::default();
+        assert_eq!(transform(&engine, 0), list(&[1]));
+        assert_eq!(transform(&engine, 1000), list(&[10, 0]));
+        assert_eq!(transform(&engine, 99), list(&[9, 9]));
+        assert_eq!(transform(&engine, 999), list(&[2021976]));
+    }
+
+    #[test]
+    fn memoised_count_matches_histogram() {
+        let mut engine = StoneEngine::default();
+        let example = stones(&[0, 1, 10, 99, 999]);
+        for blinks in [0, 1, 5, 30] {
+            let histogram = engine.histogram(&example, blinks);
+            assert_eq!(
+                engine.count_all(&example, blinks),
+                histogram.values().sum::<BigUint>()
+            );
+        }
+    }
+
+    #[test]
+    fn variant_rules() {
+        let mut engine = StoneEngine::new(vec![
+            Box::new(Replace {
+                from: Stone::from(0_u32),
+                to: Stone::from(1_u32),
+            }),
+            Box::new(SplitDigits { parts: 3 }),
+            Box::new(Multiply { factor: 7 }),
+        ]);
+        assert_eq!(transform(&engine, 123456), list(&[12, 34, 56]));
+        assert_eq!(transform(&engine, 100200), list(&[10, 2, 0]));
+        assert_eq!(transform(&engine, 12), list(&[84]));
+
+        let values = [125, 17];
+        for blinks in [1, 10, 20] {
+            assert_eq!(
+                engine.count_all(&stones(&values), blinks),
+                BigUint::from(naive_count(&engine, &values, blinks))
+            );
+        }
+    }
+
+    #[test]
+    fn large_multipliers() {
+        let args = ["--multiplier", "1000000000000"].map(String::from);
+        let mut engine = StoneEngine::new(rules_from_args(&args));
+        // odd numbers of digits stay odd times 10^12, so stones are never split and soon
+        // grow past u64
+        assert_eq!(
+            transform(&engine, 1234567890123456789),
+            vec![Stone::from(1234567890123456789_u64) * 1_000_000_000_000_u64]
+        );
+        let values = [1234567];
+        assert_eq!(
+            engine.count_all(&stones(&values), 3),
+            BigUint::from(naive_count(&engine, &values, 3))
+        );
+        assert_eq!(engine.count_all(&stones(&values), 3), BigUint::from(1_u32));
+    }
+
+    proptest! {
+        #[test]
+        fn counting_agrees_with_naive_blinking(
+            values in prop::collection::vec(0_u64..1_000_000, 1..6),
+            blinks in 0_u32..16,
+        ) {
+            let mut engine = StoneEngine::default();
+            let expected = BigUint::from(naive_count(&engine, &values, blinks));
+            prop_assert_eq!(engine.count_all(&stones(&values), blinks), expected.clone());
+            let histogram = engine.histogram(&stones(&values), blinks);
+            prop_assert_eq!(histogram.values().sum::<BigUint>(), expected);
+        }
     }
-    stones.into_values().sum()
 }
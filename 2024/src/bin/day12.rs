@@ -1,20 +1,81 @@
 use std::{
-    collections::HashSet,
-    fs::File,
+    collections::{HashMap, HashSet},
+    fmt::Write,
+    fs::{self, File},
     io::{BufRead, BufReader},
+    path::Path,
 };
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+const ORTHOGONAL: [(isize, isize); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+const ALL_DIRECTIONS: [(isize, isize); 8] = [
+    (1, 0),
+    (-1, 0),
+    (0, 1),
+    (0, -1),
+    (1, 1),
+    (1, -1),
+    (-1, 1),
+    (-1, -1),
+];
+
+// size of a plot in the svg, in pixels
+const PLOT_SIZE: usize = 24;
+// how far each fence is drawn inside its region, so fences on either side of a boundary
+// don't overlap
+const FENCE_INSET: f64 = 3.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 struct Plot {
     x: usize,
     y: usize,
 }
 
-struct PlotNeighbours {
-    up: Option<Plot>,
-    down: Option<Plot>,
-    right: Option<Plot>,
-    left: Option<Plot>,
+// which plots of the same plant belong to one region
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum Contact {
+    // plots sharing an edge
+    #[default]
+    Orthogonal,
+    // plots sharing an edge or a corner
+    Diagonal,
+}
+
+impl Contact {
+    fn directions(&self) -> &'static [(isize, isize)] {
+        match self {
+            Contact::Orthogonal => &ORTHOGONAL,
+            Contact::Diagonal => &ALL_DIRECTIONS,
+        }
+    }
+}
+
+// the side of a fence facing away from its region
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+enum Facing {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+// a straight run of fence between two corners of the grid. Corner (x, y) is the top left
+// corner of plot (x, y).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Fence {
+    from: (usize, usize),
+    to: (usize, usize),
+    facing: Facing,
+}
+
+#[derive(Clone, Debug)]
+struct RegionReport {
+    plant: char,
+    plots: Vec<Plot>,
+    area: u64,
+    perimeter: u64,
+    sides: u64,
+    holes: u64,
+    fences: Vec<Fence>,
 }
 
 impl Plot {
@@ -22,107 +83,206 @@ impl Plot {
         Plot { x, y }
     }
 
-    fn is_in_map(&self, map: &Vec<Vec<char>>) -> bool {
+    fn offset(&self, dx: isize, dy: isize) -> Option<Self> {
+        Some(Self::new(
+            self.x.checked_add_signed(dx)?,
+            self.y.checked_add_signed(dy)?,
+        ))
+    }
+
+    fn is_in_map(&self, map: &[Vec<char>]) -> bool {
         self.x < map[0].len() && self.y < map.len()
     }
 
-    fn get_plant(&self, map: &Vec<Vec<char>>) -> Option<char> {
+    fn get_plant(&self, map: &[Vec<char>]) -> Option<char> {
         if self.is_in_map(map) {
-            Some(map[self.y as usize][self.x as usize])
+            Some(map[self.y][self.x])
         } else {
             None
         }
     }
 
-    fn is_same_plant(&self, other: &Self, map: &Vec<Vec<char>>) -> bool {
-        let plant1 = self.get_plant(map);
-        let plant2 = other.get_plant(map);
-        plant1.is_some() && plant2.is_some() && plant1.unwrap() == plant2.unwrap()
+    fn is_same_plant(&self, other: &Self, map: &[Vec<char>]) -> bool {
+        let plant = self.get_plant(map);
+        plant.is_some() && plant == other.get_plant(map)
     }
 
-    fn neighbours(&self, map: &Vec<Vec<char>>) -> Vec<Self> {
-        let map_width = map[0].len();
-        let map_height = map.len();
-
-        let mut neighbours = Vec::new();
-        if self.x + 1 < map_width {
-            neighbours.push(Self::new(self.x + 1, self.y));
-        }
-        if self.x > 0 {
-            neighbours.push(Self::new(self.x - 1, self.y));
-        }
-        if self.y + 1 < map_height {
-            neighbours.push(Self::new(self.x, self.y + 1));
-        }
-        if self.y > 0 {
-            neighbours.push(Self::new(self.x, self.y - 1));
-        }
-        neighbours
-            .into_iter()
+    fn neighbours(&self, map: &[Vec<char>], contact: Contact) -> Vec<Self> {
+        contact
+            .directions()
+            .iter()
+            .filter_map(|(dx, dy)| self.offset(*dx, *dy))
             .filter(|p| p.is_same_plant(self, map))
             .collect()
     }
 
-    fn neighbours2(&self, map: &Vec<Vec<char>>) -> PlotNeighbours {
-        let mut neighbours = PlotNeighbours {
-            up: None,
-            down: None,
-            right: None,
-            left: None,
-        };
-
-        if self.y > 0 {
-            let up = Self::new(self.x, self.y - 1);
-            if up.is_same_plant(self, map) {
-                neighbours.up = Some(up);
+    fn get_plots_in_region(&self, map: &[Vec<char>], contact: Contact) -> Vec<Self> {
+        let mut visited = HashSet::from([*self]);
+        let mut stack = vec![*self];
+        let mut plots = Vec::new();
+        while let Some(plot) = stack.pop() {
+            plots.push(plot);
+            for n in plot.neighbours(map, contact) {
+                if visited.insert(n) {
+                    stack.push(n);
+                }
             }
         }
-        if self.x > 0 {
-            let left = Self::new(self.x - 1, self.y);
-            if left.is_same_plant(self, map) {
-                neighbours.left = Some(left);
+        plots.sort();
+        plots
+    }
+}
+
+impl RegionReport {
+    fn new(plots: Vec<Plot>, map: &[Vec<char>], contact: Contact) -> Self {
+        let region: HashSet<Plot> = plots.iter().copied().collect();
+
+        // unit lengths of fence, grouped by the line they lie on
+        let mut edges: HashMap<(Facing, usize), Vec<usize>> = HashMap::new();
+        let mut perimeter = 0;
+        for plot in &plots {
+            for (facing, (dx, dy)) in [
+                (Facing::Up, (0, -1)),
+                (Facing::Down, (0, 1)),
+                (Facing::Left, (-1, 0)),
+                (Facing::Right, (1, 0)),
+            ] {
+                if plot.offset(dx, dy).is_some_and(|n| region.contains(&n)) {
+                    continue;
+                }
+                perimeter += 1;
+                let (line, start) = match facing {
+                    Facing::Up => (plot.y, plot.x),
+                    Facing::Down => (plot.y + 1, plot.x),
+                    Facing::Left => (plot.x, plot.y),
+                    Facing::Right => (plot.x + 1, plot.y),
+                };
+                edges.entry((facing, line)).or_default().push(start);
             }
         }
-        let down = Self::new(self.x, self.y + 1);
-        if down.is_same_plant(self, map) {
-            neighbours.down = Some(down);
+
+        // a side is a run of consecutive unit lengths on the same line facing the same way.
+        // Two regions touching at a corner have fences on the same line facing opposite ways,
+        // so they are never merged into one side.
+        let mut fences = Vec::new();
+        for ((facing, line), mut starts) in edges {
+            starts.sort();
+            let mut run_start = starts[0];
+            for (i, start) in starts.iter().enumerate() {
+                let end = start + 1;
+                if starts.get(i + 1) == Some(&end) {
+                    continue;
+                }
+                let (from, to) = match facing {
+                    Facing::Up | Facing::Down => ((run_start, line), (end, line)),
+                    Facing::Left | Facing::Right => ((line, run_start), (line, end)),
+                };
+                fences.push(Fence { from, to, facing });
+                if let Some(next) = starts.get(i + 1) {
+                    run_start = *next;
+                }
+            }
         }
-        let right = Self::new(self.x + 1, self.y);
-        if right.is_same_plant(self, map) {
-            neighbours.right = Some(right);
+        fences.sort();
+
+        Self {
+            plant: plots[0].get_plant(map).unwrap(),
+            area: plots.len() as u64,
+            perimeter,
+            sides: fences.len() as u64,
+            holes: count_holes(&region, contact),
+            fences,
+            plots,
         }
-        neighbours
     }
+}
 
-    fn get_plots_in_region(&self, map: &Vec<Vec<char>>) -> Vec<Self> {
-        let mut visited: HashSet<Self> = HashSet::new();
-        self.plots_in_region(map, &mut visited)
-    }
+// number of pockets of other plots fully enclosed by the region
+fn count_holes(region: &HashSet<Plot>, contact: Contact) -> u64 {
+    let min_x = region.iter().map(|p| p.x).min().unwrap();
+    let max_x = region.iter().map(|p| p.x).max().unwrap();
+    let min_y = region.iter().map(|p| p.y).min().unwrap();
+    let max_y = region.iter().map(|p| p.y).max().unwrap();
+
+    // the region's bounding box with a border of one plot all round, so everything outside
+    // the region is connected through the border
+    let width = max_x - min_x + 3;
+    let height = max_y - min_y + 3;
+    let in_region = |x: usize, y: usize| {
+        x > 0 && y > 0 && region.contains(&Plot::new(x + min_x - 1, y + min_y - 1))
+    };
+
+    // whatever isn't part of the region can slip between two plots that only touch at a
+    // corner unless those plots count as connected
+    let directions: &[(isize, isize)] = match contact {
+        Contact::Orthogonal => &ALL_DIRECTIONS,
+        Contact::Diagonal => &ORTHOGONAL,
+    };
 
-    fn plots_in_region(&self, map: &Vec<Vec<char>>, visited: &mut HashSet<Self>) -> Vec<Self> {
-        if visited.contains(self) {
-            return vec![];
+    let mut seen = vec![vec![false; width]; height];
+    let mut components = 0;
+    for y in 0..height {
+        for x in 0..width {
+            if seen[y][x] || in_region(x, y) {
+                continue;
+            }
+            components += 1;
+            seen[y][x] = true;
+            let mut stack = vec![(x, y)];
+            while let Some((x, y)) = stack.pop() {
+                for (dx, dy) in directions {
+                    let (Some(nx), Some(ny)) =
+                        (x.checked_add_signed(*dx), y.checked_add_signed(*dy))
+                    else {
+                        continue;
+                    };
+                    if nx < width && ny < height && !seen[ny][nx] && !in_region(nx, ny) {
+                        seen[ny][nx] = true;
+                        stack.push((nx, ny));
+                    }
+                }
+            }
         }
-        visited.insert(*self);
-        let mut plots = self
-            .neighbours(map)
-            .into_iter()
-            .map(|plot| plot.plots_in_region(map, visited))
-            .flatten()
-            .collect::<Vec<_>>();
-        plots.push(*self);
-        plots
     }
+    // the first component found starts in the top left corner, outside the region
+    components - 1
 }
 
 fn main() {
     let path = "inputs/day12";
     let map = parse_map(path);
 
-    println!("part 1: total fencing price: {:?}", solve_part1(&map));
+    // `--diagonal` lets plots of the same plant touching at a corner form one region,
+    // `--report` lists every region with its fences and `--svg [path]` draws them
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let contact = if args.iter().any(|a| a == "--diagonal") {
+        Contact::Diagonal
+    } else {
+        Contact::Orthogonal
+    };
+
+    if let Some(i) = args.iter().position(|a| a == "--svg") {
+        let output = args
+            .get(i + 1)
+            .filter(|a| !a.starts_with("--"))
+            .map(String::as_str)
+            .unwrap_or("outputs/day12/regions.svg");
+        save_svg(&map, &regions(&map, contact), Path::new(output));
+        println!("saved regions to {output}");
+    }
+
+    if args.iter().any(|a| a == "--report") {
+        report(&map, contact);
+        return;
+    }
+
+    println!(
+        "part 1: total fencing price: {:?}",
+        solve_part1(&map, contact)
+    );
     println!(
         "part 2: total discounted fencing price: {:?}",
-        solve_part2(&map)
+        solve_part2(&map, contact)
     );
 }
 
@@ -139,110 +299,316 @@ fn parse_map(path: &str) -> Vec<Vec<char>> {
         .collect()
 }
 
-fn region_perimeter(region: &Vec<Plot>, map: &Vec<Vec<char>>) -> u64 {
-    let mut perimeter = 0;
-    region
-        .into_iter()
-        .for_each(|plot| perimeter += (4 - plot.neighbours(map).len()) as u64);
-    perimeter
-}
-
-fn solve_part1(map: &Vec<Vec<char>>) -> u64 {
-    let map_width = map[0].len();
-    let map_height = map.len();
-
-    let mut fencing_price = 0;
+fn regions(map: &[Vec<char>], contact: Contact) -> Vec<RegionReport> {
     let mut visited: HashSet<Plot> = HashSet::new();
+    let mut regions = Vec::new();
 
-    for y in 0..map_height {
-        for x in 0..map_width {
+    for y in 0..map.len() {
+        for x in 0..map[0].len() {
             if visited.contains(&Plot::new(x, y)) {
                 continue;
             }
+            let plots = Plot::new(x, y).get_plots_in_region(map, contact);
+            visited.extend(plots.iter().copied());
+            regions.push(RegionReport::new(plots, map, contact));
+        }
+    }
+    regions
+}
+
+fn report(map: &[Vec<char>], contact: Contact) {
+    for region in regions(map, contact) {
+        let Plot { x, y } = region.plots[0];
+        println!(
+            "{} at ({x}, {y}): area {}, perimeter {}, sides {}, holes {}",
+            region.plant, region.area, region.perimeter, region.sides, region.holes
+        );
+        for fence in region.fences {
+            println!(
+                "  ({}, {}) -> ({}, {}) facing {:?}",
+                fence.from.0, fence.from.1, fence.to.0, fence.to.1, fence.facing
+            );
+        }
+    }
+}
 
-            let plot = Plot::new(x, y);
-            let region = plot.get_plots_in_region(map);
-            let area = region.len() as u64;
-            let perimeter = region_perimeter(&region, map);
+// each plant gets its own hue, with neighbouring letters far apart on the colour wheel
+fn plant_color(plant: char) -> String {
+    let hue = (plant as u32 * 137) % 360;
+    format!("hsl({hue}, 60%, 75%)")
+}
 
-            fencing_price += area * perimeter;
+fn fence_color(facing: Facing) -> &'static str {
+    match facing {
+        Facing::Up => "#c0392b",
+        Facing::Down => "#2471a3",
+        Facing::Left => "#1e8449",
+        Facing::Right => "#7d3c98",
+    }
+}
 
-            region.into_iter().for_each(|plot| {
-                visited.insert(plot);
-            });
-        }
+// the plant as it can appear in svg text, which can't hold a bare `<` or `&`
+fn escape(plant: char) -> String {
+    match plant {
+        '<' => "&lt;".to_string(),
+        '>' => "&gt;".to_string(),
+        '&' => "&amp;".to_string(),
+        '"' => "&quot;".to_string(),
+        '\'' => "&apos;".to_string(),
+        _ => plant.to_string(),
     }
-    fencing_price
 }
 
-fn region_sides(region: &Vec<Plot>, map: &Vec<Vec<char>>) -> u64 {
-    let mut sides = 0;
-    region.into_iter().for_each(|plot| {
-        let neighbours = plot.neighbours2(map);
-        if neighbours.left.is_none() && neighbours.up.is_none() {
-            sides += 1;
-        }
-        if neighbours.left.is_none() && neighbours.down.is_none() {
-            sides += 1;
-        }
-        if neighbours.right.is_none() && neighbours.up.is_none() {
-            sides += 1;
-        }
-        if neighbours.right.is_none() && neighbours.down.is_none() {
-            sides += 1;
-        }
-        if neighbours.left.is_some()
-            && neighbours.up.is_some()
-            && !Plot::new(plot.x - 1, plot.y - 1).is_same_plant(plot, map)
-        {
-            sides += 1;
-        }
-        if neighbours.left.is_some()
-            && neighbours.down.is_some()
-            && !Plot::new(plot.x - 1, plot.y + 1).is_same_plant(plot, map)
-        {
-            sides += 1;
-        }
-        if neighbours.right.is_some()
-            && neighbours.up.is_some()
-            && !Plot::new(plot.x + 1, plot.y - 1).is_same_plant(plot, map)
-        {
-            sides += 1;
+// draws the garden with every region filled in the colour of its plant, every side of its
+// fence as a separate line ending in dots, and a label with area × perimeter / sides on the
+// plot closest to the middle of the region
+fn render_svg(map: &[Vec<char>], regions: &[RegionReport]) -> String {
+    let width = map[0].len() * PLOT_SIZE;
+    let height = map.len() * PLOT_SIZE;
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#
+    )
+    .unwrap();
+    writeln!(svg, r#"<rect width="100%" height="100%" fill="white"/>"#).unwrap();
+
+    // fences and labels go on top of every region's plots
+    let mut fences = String::new();
+    let mut labels = String::new();
+
+    for region in regions {
+        writeln!(
+            svg,
+            r#"<g><title>{}: area {}, perimeter {}, sides {}, holes {}</title>"#,
+            escape(region.plant),
+            region.area,
+            region.perimeter,
+            region.sides,
+            region.holes
+        )
+        .unwrap();
+        let fill = plant_color(region.plant);
+        for plot in &region.plots {
+            writeln!(
+                svg,
+                r#"<rect x="{}" y="{}" width="{PLOT_SIZE}" height="{PLOT_SIZE}" fill="{fill}"/>"#,
+                plot.x * PLOT_SIZE,
+                plot.y * PLOT_SIZE
+            )
+            .unwrap();
         }
-        if neighbours.right.is_some()
-            && neighbours.down.is_some()
-            && !Plot::new(plot.x + 1, plot.y + 1).is_same_plant(plot, map)
-        {
-            sides += 1;
+        writeln!(svg, "</g>").unwrap();
+
+        for fence in &region.fences {
+            // move the fence off the boundary, towards the region it belongs to
+            let (dx, dy) = match fence.facing {
+                Facing::Up => (0.0, FENCE_INSET),
+                Facing::Down => (0.0, -FENCE_INSET),
+                Facing::Left => (FENCE_INSET, 0.0),
+                Facing::Right => (-FENCE_INSET, 0.0),
+            };
+            // and shorten it a little, so consecutive sides don't run into each other
+            let (sx, sy) = match fence.facing {
+                Facing::Up | Facing::Down => (FENCE_INSET, 0.0),
+                Facing::Left | Facing::Right => (0.0, FENCE_INSET),
+            };
+            let x1 = (fence.from.0 * PLOT_SIZE) as f64 + dx + sx;
+            let y1 = (fence.from.1 * PLOT_SIZE) as f64 + dy + sy;
+            let x2 = (fence.to.0 * PLOT_SIZE) as f64 + dx - sx;
+            let y2 = (fence.to.1 * PLOT_SIZE) as f64 + dy - sy;
+            let color = fence_color(fence.facing);
+            writeln!(
+                fences,
+                r#"<line x1="{x1}" y1="{y1}" x2="{x2}" y2="{y2}" stroke="{color}" stroke-width="2"/>"#
+            )
+            .unwrap();
+            for (x, y) in [(x1, y1), (x2, y2)] {
+                writeln!(
+                    fences,
+                    r#"<circle cx="{x}" cy="{y}" r="2" fill="{color}"/>"#
+                )
+                .unwrap();
+            }
         }
-    });
-    sides
+
+        let count = region.plots.len() as f64;
+        let mid_x = region.plots.iter().map(|p| p.x as f64).sum::<f64>() / count;
+        let mid_y = region.plots.iter().map(|p| p.y as f64).sum::<f64>() / count;
+        let label_plot = region
+            .plots
+            .iter()
+            .min_by(|a, b| {
+                let distance =
+                    |p: &Plot| (p.x as f64 - mid_x).powi(2) + (p.y as f64 - mid_y).powi(2);
+                distance(a).total_cmp(&distance(b))
+            })
+            .unwrap();
+        writeln!(
+            labels,
+            r#"<text x="{}" y="{}" font-family="monospace" font-size="8" text-anchor="middle" dominant-baseline="middle">{} {}×{} / {}</text>"#,
+            label_plot.x * PLOT_SIZE + PLOT_SIZE / 2,
+            label_plot.y * PLOT_SIZE + PLOT_SIZE / 2,
+            escape(region.plant),
+            region.area,
+            region.perimeter,
+            region.sides
+        )
+        .unwrap();
+    }
+
+    svg.push_str(&fences);
+    svg.push_str(&labels);
+    writeln!(svg, "</svg>").unwrap();
+    svg
 }
 
-fn solve_part2(map: &Vec<Vec<char>>) -> u64 {
-    let map_width = map[0].len();
-    let map_height = map.len();
+fn save_svg(map: &[Vec<char>], regions: &[RegionReport], path: &Path) {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).expect("couldn't create output directory");
+    }
+    fs::write(path, render_svg(map, regions)).expect("couldn't write svg");
+}
 
-    let mut fencing_price = 0;
-    let mut visited: HashSet<Plot> = HashSet::new();
+fn solve_part1(map: &[Vec<char>], contact: Contact) -> u64 {
+    regions(map, contact)
+        .iter()
+        .map(|region| region.area * region.perimeter)
+        .sum()
+}
 
-    for y in 0..map_height {
-        for x in 0..map_width {
-            if visited.contains(&Plot::new(x, y)) {
-                continue;
-            }
+fn solve_part2(map: &[Vec<char>], contact: Contact) -> u64 {
+    regions(map, contact)
+        .iter()
+        .map(|region| region.area * region.sides)
+        .sum()
+}
 
-            let plot = Plot::new(x, y);
-            let region = plot.get_plots_in_region(map);
-            let area = region.len() as u64;
-            let sides = region_sides(&region, map);
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-            fencing_price += area * sides;
+    fn garden(rows: &[&str]) -> Vec<Vec<char>> {
+        rows.iter().map(|row| row.chars().collect()).collect()
+    }
 
-            region.into_iter().for_each(|plot| {
-                visited.insert(plot);
-            });
-        }
+    fn region_at(regions: &[RegionReport], x: usize, y: usize) -> &RegionReport {
+        regions
+            .iter()
+            .find(|r| r.plots.contains(&Plot::new(x, y)))
+            .unwrap()
+    }
+
+    #[test]
+    fn small_example() {
+        let map = garden(&["AAAA", "BBCD", "BBCC", "EEEC"]);
+        assert_eq!(solve_part1(&map, Contact::Orthogonal), 140);
+        assert_eq!(solve_part2(&map, Contact::Orthogonal), 80);
+
+        let regions = regions(&map, Contact::Orthogonal);
+        assert_eq!(regions.len(), 5);
+        let c = region_at(&regions, 2, 1);
+        assert_eq!((c.plant, c.area, c.perimeter, c.sides), ('C', 4, 10, 8));
+    }
+
+    #[test]
+    fn svg_fences_and_labels() {
+        let map = garden(&["AAAA", "BBCD", "BBCC", "EEEC"]);
+        let svg = render_svg(&map, &regions(&map, Contact::Orthogonal));
+        // the D plot at (3, 1) is fenced on every side, each fence inset into the plot
+        assert!(svg.contains("<title>D: area 1, perimeter 4, sides 4, holes 0</title>"));
+        assert!(svg.contains(&format!(
+            r#"<line x1="75" y1="27" x2="93" y2="27" stroke="{}" stroke-width="2"/>"#,
+            fence_color(Facing::Up)
+        )));
+        assert!(svg.contains(&format!(
+            r#"<line x1="93" y1="27" x2="93" y2="45" stroke="{}" stroke-width="2"/>"#,
+            fence_color(Facing::Right)
+        )));
+        assert!(svg.contains(r#"<text x="84" y="36" font-family="monospace" font-size="8" text-anchor="middle" dominant-baseline="middle">D 1×4 / 4</text>"#));
+        assert_eq!(svg.matches("<line").count(), 4 + 4 + 8 + 4 + 4);
+    }
+
+    #[test]
+    fn svg_escapes_plants() {
+        let map = garden(&["<&"]);
+        let svg = render_svg(&map, &regions(&map, Contact::Orthogonal));
+        assert!(svg.contains("<title>&lt;: area 1"));
+        assert!(svg.contains(">&amp; 1×4 / 4</text>"));
+        assert!(!svg.contains("><:") && !svg.contains(">& "));
+    }
+
+    #[test]
+    fn nested_regions() {
+        let map = garden(&["OOOOO", "OXOXO", "OOOOO", "OXOXO", "OOOOO"]);
+        assert_eq!(solve_part1(&map, Contact::Orthogonal), 772);
+        assert_eq!(solve_part2(&map, Contact::Orthogonal), 436);
+
+        let regions = regions(&map, Contact::Orthogonal);
+        let o = region_at(&regions, 0, 0);
+        assert_eq!((o.area, o.perimeter, o.sides, o.holes), (21, 36, 20, 4));
+        assert_eq!(region_at(&regions, 1, 1).holes, 0);
+    }
+
+    #[test]
+    fn larger_example() {
+        let map = garden(&[
+            "RRRRIICCFF",
+            "RRRRIICCCF",
+            "VVRRRCCFFF",
+            "VVRCCCJFFF",
+            "VVVVCJJCFE",
+            "VVIVCCJJEE",
+            "VVIIICJJEE",
+            "MIIIIIJJEE",
+            "MIIISIJEEE",
+            "MMMISSJEEE",
+        ]);
+        assert_eq!(solve_part1(&map, Contact::Orthogonal), 1930);
+        assert_eq!(solve_part2(&map, Contact::Orthogonal), 1206);
+    }
+
+    #[test]
+    fn e_shaped_region() {
+        let map = garden(&["EEEEE", "EXXXX", "EEEEE", "EXXXX", "EEEEE"]);
+        assert_eq!(solve_part2(&map, Contact::Orthogonal), 236);
+    }
+
+    #[test]
+    fn regions_touching_at_a_corner() {
+        let map = garden(&["AAAAAA", "AAABBA", "AAABBA", "ABBAAA", "ABBAAA", "AAAAAA"]);
+        assert_eq!(solve_part2(&map, Contact::Orthogonal), 368);
+
+        let regions = regions(&map, Contact::Orthogonal);
+        let a = region_at(&regions, 0, 0);
+        assert_eq!((a.area, a.sides, a.holes), (28, 12, 1));
+        // the fences either side of the corner the B regions share stay separate sides
+        assert!(a.fences.contains(&Fence {
+            from: (1, 3),
+            to: (3, 3),
+            facing: Facing::Down
+        }));
+        assert!(a.fences.contains(&Fence {
+            from: (3, 3),
+            to: (5, 3),
+            facing: Facing::Up
+        }));
+    }
+
+    #[test]
+    fn diagonal_contact_merges_regions() {
+        let map = garden(&["AAAAAA", "AAABBA", "AAABBA", "ABBAAA", "ABBAAA", "AAAAAA"]);
+        let regions = regions(&map, Contact::Diagonal);
+        assert_eq!(regions.len(), 2);
+        let b = region_at(&regions, 1, 3);
+        assert_eq!((b.area, b.perimeter, b.sides), (8, 16, 8));
+        // the A plots now connect across the corner the B plots share, splitting the hole
+        // in two
+        assert_eq!(region_at(&regions, 0, 0).holes, 2);
+
+        let checkerboard = garden(&["AB", "BA"]);
+        assert_eq!(solve_part1(&checkerboard, Contact::Diagonal), 2 * 2 * 8);
+        assert_eq!(solve_part2(&checkerboard, Contact::Diagonal), 2 * 2 * 8);
+        assert_eq!(solve_part1(&checkerboard, Contact::Orthogonal), 4 * 4);
     }
-    fencing_price
 }
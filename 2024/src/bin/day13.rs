@@ -1,6 +1,9 @@
-use std::{fs::File, io::Read};
+use std::fs;
 
-use regex::Regex;
+use aoc2024::{
+    parse::ParseError,
+    template::{parse_records, FromRecord, Record},
+};
 
 #[derive(Debug)]
 struct Equation {
@@ -67,64 +70,54 @@ fn main() {
     println!("part 2: fewest tokens: {:?}", solve_part2(&machines));
 }
 
-fn parse_input(path: &str) -> Vec<(Equation, Equation)> {
-    let mut file = File::open(path).expect("couldn't open input file");
-    let mut buf = String::new();
-    file.read_to_string(&mut buf)
-        .expect("problem reaading input file");
-
-    let re = Regex::new(
-        r#"Button A: X\+(\d+), Y\+(\d+)
-Button B: X\+(\d+), Y\+(\d+)
-Prize: X=(\d+), Y=(\d+)"#,
-    )
-    .unwrap();
-
-    re.captures_iter(&buf)
-        .map(|caps| {
-            let (_, [a1, a2, b1, b2, c1, c2]) = caps.extract::<6>();
-            let [a1, b1, c1] = [
-                a1.parse().unwrap(),
-                b1.parse().unwrap(),
-                c1.parse().unwrap(),
-            ];
-            let eq1 = Equation::new(a1, b1, c1);
-            let [a2, b2, c2] = [
-                a2.parse().unwrap(),
-                b2.parse().unwrap(),
-                c2.parse().unwrap(),
-            ];
-            let eq2 = Equation::new(a2, b2, c2);
-            return (eq1, eq2);
+/// A claw machine, as the equations for where the claw ends up along each axis.
+#[derive(Debug)]
+struct Machine {
+    x: Equation,
+    y: Equation,
+}
+
+impl FromRecord for Machine {
+    const TEMPLATE: &'static str = "\
+Button A: X+{ax}, Y+{ay}
+Button B: X+{bx}, Y+{by}
+Prize: X={px}, Y={py}";
+
+    fn from_record(record: &Record) -> Result<Self, ParseError> {
+        Ok(Self {
+            x: Equation::new(record.get("ax")?, record.get("bx")?, record.get("px")?),
+            y: Equation::new(record.get("ay")?, record.get("by")?, record.get("py")?),
         })
-        .collect()
+    }
+}
+
+fn parse_input(path: &str) -> Vec<Machine> {
+    let input = fs::read_to_string(path).expect("couldn't read input file");
+    parse_records(&input).unwrap_or_else(|e| panic!("invalid machine at {e}"))
 }
 
-fn solve_part1(machines: &Vec<(Equation, Equation)>) -> u64 {
+fn solve_part1(machines: &[Machine]) -> u64 {
     machines
         .iter()
-        .map(|m| {
-            let (eq1, eq2) = m;
+        .map(|Machine { x: eq1, y: eq2 }| {
             let (a, b) = Equation::solve_simultaneous(eq1, eq2);
-            if a >= 0
-                && a <= 100
-                && b >= 0
-                && b <= 100
+            if (0..=100).contains(&a)
+                && (0..=100).contains(&b)
                 && (a * eq1.a + b * eq1.b == eq1.c)
                 && (a * eq2.a + b * eq2.b == eq2.c)
             {
-                return ((a * 3) + b) as u64;
+                ((a * 3) + b) as u64
+            } else {
+                0
             }
-            return 0;
         })
         .sum()
 }
 
-fn solve_part2(machines: &Vec<(Equation, Equation)>) -> u64 {
+fn solve_part2(machines: &[Machine]) -> u64 {
     machines
         .iter()
-        .map(|m| {
-            let (eq1, eq2) = m;
+        .map(|Machine { x: eq1, y: eq2 }| {
             let eq1 = Equation {
                 c: eq1.c + 10000000000000,
                 ..*eq1
@@ -139,9 +132,50 @@ fn solve_part2(machines: &Vec<(Equation, Equation)>) -> u64 {
                 && (a * eq1.a + b * eq1.b == eq1.c)
                 && (a * eq2.a + b * eq2.b == eq2.c)
             {
-                return ((a * 3) + b) as u64;
+                ((a * 3) + b) as u64
+            } else {
+                0
             }
-            return 0;
         })
         .sum()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aoc2024::gen::{claw_machines, Rng};
+
+    #[test]
+    fn example() {
+        let machines = parse_records::<Machine>(
+            "\
+Button A: X+94, Y+34
+Button B: X+22, Y+67
+Prize: X=8400, Y=5400
+
+Button A: X+26, Y+66
+Button B: X+67, Y+21
+Prize: X=12748, Y=12176
+
+Button A: X+17, Y+86
+Button B: X+84, Y+37
+Prize: X=7870, Y=6450
+
+Button A: X+69, Y+23
+Button B: X+27, Y+71
+Prize: X=18641, Y=10279",
+        )
+        .unwrap();
+        assert_eq!(solve_part1(&machines), 480);
+    }
+
+    #[test]
+    fn generated_answers() {
+        for seed in 1..20 {
+            let (input, [part1, part2]) = claw_machines(&mut Rng::new(seed), 20);
+            let machines = parse_records::<Machine>(&input).unwrap();
+            assert_eq!(solve_part1(&machines), part1);
+            assert_eq!(solve_part2(&machines), part2);
+        }
+    }
+}
@@ -1,10 +1,11 @@
-use std::{
-    fs::File,
-    io::{BufRead, BufReader},
-};
+use std::{fs, io, ops::RangeInclusive};
 
-use image::RgbImage;
-use regex::Regex;
+use aoc2024::{
+    parse::{self, ParseError},
+    render::{open_sink, ColorMap, Format, Frame, RenderOptions},
+    template::{parse_records, FromRecord, Record},
+    visualize::{Canvas, VisualizeOptions, Visualizer},
+};
 
 // const MAP_WIDTH: i32 = 11;
 // const MAP_HEIGHT: i32 = 7;
@@ -68,43 +69,112 @@ impl Robot {
 
 fn main() {
     let path = "inputs/day14";
-    let mut robots = parse_input(path);
+    let robots = parse_input(path);
+
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let visualize = VisualizeOptions::extract(&mut args);
+    let args = RenderArgs::from_args(args.into_iter());
+
+    if let Some(options) = visualize {
+        visualize_robots(&robots, &args.steps, options).expect("couldn't visualize robots");
+        return;
+    }
 
-    println!("part 1: safety factor: {:?}", solve_part1(&mut robots));
-    part2(&robots);
+    println!("part 1: safety factor: {:?}", solve_part1(&robots));
+    part2(&robots, &args);
 }
 
-fn parse_input(path: &str) -> Vec<Robot> {
-    let file = File::open(path).expect("couldn't open input file");
-    let reader = BufReader::new(file);
-
-    let re = Regex::new(r"-?\d+").unwrap();
-
-    reader
-        .lines()
-        .map(|line| {
-            let line = line.expect("problem reading line in input file");
-            let values: Vec<i32> = re
-                .find_iter(&line)
-                .filter_map(|m| m.as_str().parse().ok())
-                .collect();
-
-            return Robot {
-                position: Position {
-                    x: values[0],
-                    y: values[1],
-                },
-                velocity: Velocity {
-                    x: values[2],
-                    y: values[3],
-                },
+struct RenderArgs {
+    format: Format,
+    steps: RangeInclusive<u32>,
+    output: String,
+    options: RenderOptions,
+}
+
+impl RenderArgs {
+    // e.g. `--format apng --steps 7000..=7100 --scale 4 --colors heat --output robots.png`
+    fn from_args(mut args: impl Iterator<Item = String>) -> Self {
+        let mut format = Format::Gif;
+        let mut steps = 0..=10000;
+        let mut output = None;
+        let mut options = RenderOptions::default();
+
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .unwrap_or_else(|| panic!("missing value for {arg}"))
             };
+            match arg.as_str() {
+                "--format" => {
+                    let name = value();
+                    format = Format::from_name(&name)
+                        .unwrap_or_else(|| panic!("unknown format '{name}'"));
+                }
+                "--steps" => {
+                    let value = value();
+                    // every step up to the last is simulated, so the range needs an end
+                    steps = parse::range(&value)
+                        .filter(|_| !value.ends_with(".."))
+                        .unwrap_or_else(|| panic!("invalid step range '{value}'"));
+                }
+                "--scale" => {
+                    options.scale = value()
+                        .parse()
+                        .ok()
+                        .filter(|scale| *scale != 0)
+                        .expect("invalid scale")
+                }
+                "--delay" => options.frame_delay_ms = value().parse().expect("invalid delay"),
+                "--colors" => {
+                    options.color_map = match value().as_str() {
+                        "mono" => ColorMap::Mono,
+                        "heat" => ColorMap::Heat,
+                        name => panic!("unknown color map '{name}'"),
+                    }
+                }
+                "--output" => output = Some(value()),
+                _ => panic!("unknown argument '{arg}'"),
+            }
+        }
+
+        let output = output.unwrap_or_else(|| match format {
+            Format::PngSequence => "outputs/day14/robots".to_string(),
+            Format::Gif => "outputs/day14/robots.gif".to_string(),
+            Format::Apng => "outputs/day14/robots.png".to_string(),
+        });
+        Self {
+            format,
+            steps,
+            output,
+            options,
+        }
+    }
+}
+
+impl FromRecord for Robot {
+    const TEMPLATE: &'static str = "p={px},{py} v={vx},{vy}";
+
+    fn from_record(record: &Record) -> Result<Self, ParseError> {
+        Ok(Self {
+            position: Position {
+                x: record.get("px")?,
+                y: record.get("py")?,
+            },
+            velocity: Velocity {
+                x: record.get("vx")?,
+                y: record.get("vy")?,
+            },
         })
-        .collect()
+    }
 }
 
-fn solve_part1(robots: &Vec<Robot>) -> u64 {
-    let mut robots = robots.clone();
+fn parse_input(path: &str) -> Vec<Robot> {
+    let input = fs::read_to_string(path).expect("couldn't read input file");
+    parse_records(&input).unwrap_or_else(|e| panic!("invalid robot at {e}"))
+}
+
+fn solve_part1(robots: &[Robot]) -> u64 {
+    let mut robots = robots.to_vec();
     for _ in 1..=100 {
         robots.iter_mut().for_each(|robot| robot.r#move());
     }
@@ -122,26 +192,85 @@ fn solve_part1(robots: &Vec<Robot>) -> u64 {
     q1 * q2 * q3 * q4
 }
 
-fn render_robots(robots: &Vec<Robot>, i: u32) {
-    let mut img = RgbImage::new(MAP_WIDTH as u32, MAP_HEIGHT as u32);
-    for (x, y, pixel) in img.enumerate_pixels_mut() {
-        let robot_count = robots
+fn robots_frame(robots: &[Robot]) -> Frame {
+    Frame::from_points(
+        MAP_WIDTH as u32,
+        MAP_HEIGHT as u32,
+        robots
             .iter()
-            .filter(|r| r.position.x == x as i32 && r.position.y == y as i32)
-            .count();
-        if robot_count > 0 {
-            *pixel = image::Rgb([255, 255, 255]);
+            .map(|r| (r.position.x as i64, r.position.y as i64)),
+    )
+}
+
+fn part2(robots: &[Robot], args: &RenderArgs) {
+    let mut robots = robots.to_vec();
+    let (first, last) = (*args.steps.start(), *args.steps.end());
+
+    let mut sink = open_sink(
+        &args.output,
+        args.format,
+        args.steps.clone(),
+        MAP_WIDTH as u32,
+        MAP_HEIGHT as u32,
+        args.options,
+    )
+    .expect("couldn't open output");
+
+    for i in 0..=last {
+        if i > 0 {
+            robots.iter_mut().for_each(|robot| robot.r#move());
+        }
+        if i >= first {
+            sink.write_frame(&robots_frame(&robots))
+                .expect("couldn't write frame");
         }
     }
-    img.save(format!("outputs/day14/robots/{i}.png")).unwrap();
+    sink.finish().expect("couldn't finish output");
+    println!("part 2: robots rendered to {}", args.output);
 }
 
-fn part2(robots: &Vec<Robot>) {
-    let mut robots = robots.clone();
-    render_robots(&robots, 0);
+fn visualize_robots(
+    robots: &[Robot],
+    steps: &RangeInclusive<u32>,
+    options: VisualizeOptions,
+) -> io::Result<()> {
+    let mut robots = robots.to_vec();
+    let mut visualizer = Visualizer::new(options)?;
 
-    for i in 1..=10000 {
-        robots.iter_mut().for_each(|robot| robot.r#move());
-        render_robots(&robots, i);
+    for i in 0..=*steps.end() {
+        if i > 0 {
+            robots.iter_mut().for_each(|robot| robot.r#move());
+        }
+        if steps.contains(&i) && !visualizer.show(&Canvas::from_frame(&robots_frame(&robots)))? {
+            return Ok(());
+        }
+    }
+    visualizer.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render_args(args: &[&str]) -> RenderArgs {
+        RenderArgs::from_args(args.iter().map(|a| a.to_string()))
+    }
+
+    #[test]
+    fn steps_and_scale() {
+        let args = render_args(&["--steps", "5..8", "--scale", "3"]);
+        assert_eq!((args.steps, args.options.scale), (5..=7, 3));
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid step range '5..'")]
+    fn open_step_range() {
+        render_args(&["--steps", "5.."]);
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid scale")]
+    fn zero_scale() {
+        render_args(&["--scale", "0"]);
     }
 }
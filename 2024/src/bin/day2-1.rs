@@ -1,43 +1,15 @@
-use std::fs::File;
-use std::io::{self, BufRead};
+use aoc2024::day2::{parse_input, Analyser};
 
-fn main() -> io::Result<()> {
+fn main() {
     let path = "inputs/day2";
-    let mut reports = Vec::new();
-    let mut safe_reports = 0;
+    let reports = parse_input(path);
 
-    let file = File::open(path)?;
-    let reader = io::BufReader::new(file);
+    // `--removals <k>` allows up to k levels to be taken out of each report and `--steps`
+    // changes how far apart levels can be, e.g. `--steps 1..=5`
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let analyser = Analyser::from_args(&args, 0);
 
-    for line in reader.lines() {
-        let line = line?;
-        let values: Vec<u64> = line
-            .split_whitespace()
-            .filter_map(|s| s.parse::<u64>().ok())
-            .collect();
-        reports.push(values);
-    }
-
-    for report in reports {
-        if is_safe(&report) {
-            safe_reports += 1;
-        }
-    }
+    let safe_reports = reports.iter().filter(|r| analyser.is_safe(r)).count();
 
     println!("safe reports: {safe_reports}");
-
-    Ok(())
-}
-
-fn is_safe(report: &[u64]) -> bool {
-    let increasing = report[1] > report[0];
-    for i in 1..report.len() {
-        if (increasing && report[i] < report[i - 1])
-            || (!increasing && report[i] > report[i - 1])
-            || (!(1..=3).contains(&report[i].abs_diff(report[i - 1])))
-        {
-            return false;
-        }
-    }
-    true
 }
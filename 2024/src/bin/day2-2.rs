@@ -1,56 +1,30 @@
-use std::fs::File;
-use std::io::{self, BufRead};
+use aoc2024::day2::{parse_input, Analyser};
 
-fn main() -> io::Result<()> {
+fn main() {
     let path = "inputs/day2";
-    let mut reports = Vec::new();
-    let mut safe_reports = 0;
-
-    let file = File::open(path)?;
-    let reader = io::BufReader::new(file);
+    let reports = parse_input(path);
 
-    for line in reader.lines() {
-        let line = line?;
-        let values: Vec<u64> = line
-            .split_whitespace()
-            .filter_map(|s| s.parse::<u64>().ok())
-            .collect();
-        reports.push(values);
-    }
+    // `--removals <k>` allows up to k levels to be taken out of each report and `--steps`
+    // changes how far apart levels can be, e.g. `--steps 1..=5`
+    // `--explain` lists the levels taken out of each report made safe that way
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let analyser = Analyser::from_args(&args, 1);
+    let explain = args.iter().any(|a| a == "--explain");
 
-    for report in reports {
-        if is_safe(&report) {
-            safe_reports += 1;
+    let mut safe_reports = 0;
+    for (i, report) in reports.iter().enumerate() {
+        let Some(removals) = analyser.removals(report) else {
             continue;
-        }
-        for i in 0..report.len() {
-            let report = report
+        };
+        safe_reports += 1;
+        if explain && !removals.is_empty() {
+            let levels = removals
                 .iter()
-                .enumerate()
-                .filter(|(j, _)| *j != i)
-                .map(|(_, v)| *v)
+                .map(|r| format!("{} (position {})", report[*r], r + 1))
                 .collect::<Vec<_>>();
-            if is_safe(&report) {
-                safe_reports += 1;
-                break;
-            }
+            println!("report {}: remove {}", i + 1, levels.join(", "));
         }
     }
 
     println!("safe reports: {safe_reports}");
-
-    Ok(())
-}
-
-fn is_safe(report: &[u64]) -> bool {
-    let increasing = report[1] > report[0];
-    for i in 1..report.len() {
-        if (increasing && report[i] < report[i - 1])
-            || (!increasing && report[i] > report[i - 1])
-            || (!(1..=3).contains(&report[i].abs_diff(report[i - 1])))
-        {
-            return false;
-        }
-    }
-    true
 }
@@ -1,24 +1,25 @@
-use regex::Regex;
 use std::fs::File;
-use std::io::{self, BufRead};
 
-fn main() -> io::Result<()> {
-    let path = "inputs/day3";
-    let mut result = 0;
+use aoc2024::day3::{run, Language, MulOnly};
 
-    let file = File::open(path)?;
-    let reader = io::BufReader::new(file);
+fn main() {
+    let path = "inputs/day3";
+    let file = File::open(path).expect("couldn't open input file");
 
-    let re = Regex::new(r"mul\(([0-9]+),([0-9]+)\)").unwrap();
+    // `--max-digits <n>` only accepts numbers of up to n digits, `--tokens` lists every
+    // instruction found and where
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let language = Language::from_args(&args);
+    let program = language
+        .tokenize_reader(file)
+        .expect("couldn't read input file");
 
-    for line in reader.lines() {
-        let line = line?;
-        for (_, [f1, f2]) in re.captures_iter(&line).map(|caps| caps.extract()) {
-            result += f1.parse::<u64>().unwrap() * f2.parse::<u64>().unwrap();
+    if args.iter().any(|a| a == "--tokens") {
+        for token in &program {
+            println!("{}: {:?}", token.offset, token.instr);
         }
     }
 
+    let result = run(&program, &MulOnly).expect("the total is too big for a u64");
     println!("result: {result}");
-
-    Ok(())
 }
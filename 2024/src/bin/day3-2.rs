@@ -1,38 +1,25 @@
-use regex::Regex;
 use std::fs::File;
-use std::io::{self, BufRead};
 
-fn main() -> io::Result<()> {
-    let path = "inputs/day3";
-    let mut result = 0;
+use aoc2024::day3::{run, Conditional, Language};
 
-    let file = File::open(path)?;
-    let reader = io::BufReader::new(file);
+fn main() {
+    let path = "inputs/day3";
+    let file = File::open(path).expect("couldn't open input file");
 
-    let re = Regex::new(r"(do\(\))|(don't\(\))|mul\(([0-9]+,[0-9]+)\)").unwrap();
+    // `--max-digits <n>` only accepts numbers of up to n digits, `--tokens` lists every
+    // instruction found and where
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let language = Language::from_args(&args);
+    let program = language
+        .tokenize_reader(file)
+        .expect("couldn't read input file");
 
-    let mut mul_enabled = true;
-    for line in reader.lines() {
-        let line = line?;
-        for captures in re.captures_iter(&line) {
-            if let Some(_) = captures.get(1) {
-                mul_enabled = true;
-            } else if let Some(_) = captures.get(2) {
-                mul_enabled = false;
-            } else if let Some(capture) = captures.get(3) {
-                if mul_enabled {
-                    let operands: Vec<_> = capture
-                        .as_str()
-                        .split(',')
-                        .map(|s| s.parse::<u64>().unwrap())
-                        .collect();
-                    result += operands[0] * operands[1];
-                }
-            }
+    if args.iter().any(|a| a == "--tokens") {
+        for token in &program {
+            println!("{}: {:?}", token.offset, token.instr);
         }
     }
 
+    let result = run(&program, &Conditional).expect("the total is too big for a u64");
     println!("result: {result}");
-
-    Ok(())
 }
@@ -1,69 +1,24 @@
-use std::fs::File;
-use std::io::{self, BufRead};
+use aoc2024::{grid::Grid, word_search::find_word};
 
-fn main() -> io::Result<()> {
+fn main() {
     let path = "inputs/day4";
-    let mut result = 0;
-
-    let file = File::open(path)?;
-    let reader = io::BufReader::new(file);
-
-    let mut vertical_lines = Vec::new();
-    let mut diagonal_lines_lr = Vec::new();
-    let mut diagonal_lines_rl = Vec::new();
-
-    for (i, line) in reader.lines().enumerate() {
-        let line = line?;
-        result += line.matches("XMAS").count();
-        result += line.matches("SAMX").count();
-
-        for (j, ch) in line.chars().enumerate() {
-            if i == 0 {
-                vertical_lines.push(String::from(ch));
-            } else {
-                vertical_lines[j].push(ch);
-            }
-
-            if i <= j {
-                if i == 0 {
-                    diagonal_lines_lr.push(String::from(ch));
-                } else {
-                    diagonal_lines_lr[j - i].push(ch);
-                }
-            } else {
-                if j == 0 {
-                    diagonal_lines_lr.push(String::from(ch));
-                } else {
-                    diagonal_lines_lr[line.len() + i - j - 1].push(ch);
-                }
-            }
-        }
-
-        for (j, ch) in line.chars().rev().enumerate() {
-            if i <= j {
-                if i == 0 {
-                    diagonal_lines_rl.push(String::from(ch));
-                } else {
-                    diagonal_lines_rl[j - i].push(ch);
-                }
-            } else {
-                if j == 0 {
-                    diagonal_lines_rl.push(String::from(ch));
-                } else {
-                    diagonal_lines_rl[line.len() + i - j - 1].push(ch);
-                }
-            }
+    let grid = Grid::from_file(path);
+
+    // `--word <word>` searches for another word, `--matches` lists where each one is
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let word = args
+        .iter()
+        .position(|a| a == "--word")
+        .map(|i| args.get(i + 1).expect("--word needs a word").as_str())
+        .unwrap_or("XMAS");
+
+    let matches = find_word(&grid, word);
+    if args.iter().any(|a| a == "--matches") {
+        for m in &matches {
+            let (x, y) = m.cells[0];
+            println!("({x}, {y}) going {:?}", m.direction);
         }
     }
 
-    for lines in [vertical_lines, diagonal_lines_lr, diagonal_lines_rl] {
-        for line in &lines {
-            result += line.matches("XMAS").count();
-            result += line.matches("SAMX").count();
-        }
-    }
-
-    println!("result: {result}");
-
-    Ok(())
+    println!("result: {}", matches.len());
 }
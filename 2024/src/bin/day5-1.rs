@@ -1,55 +1,24 @@
-use std::collections::HashMap;
-use std::fs::File;
-use std::io::{self, BufRead};
+use aoc2024::day5::parse_input;
 
-fn main() -> io::Result<()> {
+fn main() {
     let path = "inputs/day5";
-    let mut result = 0;
-
-    let file = File::open(path)?;
-    let reader = io::BufReader::new(file);
-
-    let mut dict: HashMap<u64, Vec<u64>> = HashMap::new();
-
-    let mut lines = reader.lines();
-    while let Some(line) = lines.next() {
-        let line = line?;
-
-        if line.is_empty() {
-            break;
-        }
+    let (rules, updates) = parse_input(path);
 
-        let values = line
-            .split('|')
-            .map(|x| x.parse::<u64>().unwrap())
-            .collect::<Vec<_>>();
+    // `--explain` lists the rules each invalid update breaks
+    let explain = std::env::args().any(|a| a == "--explain");
 
-        dict.entry(values[0])
-            .and_modify(|x| x.push(values[1]))
-            .or_insert(vec![values[1]]);
-    }
-
-    'outer: while let Some(line) = lines.next() {
-        let line = line?;
-
-        let values = line
-            .split(',')
-            .map(|x| x.parse::<u64>().unwrap())
-            .collect::<Vec<_>>();
-
-        for (i, value) in values.iter().enumerate() {
-            if let Some(successors) = dict.get(value) {
-                for j in 0..i {
-                    if successors.contains(&values[j]) {
-                        continue 'outer;
-                    }
-                }
+    let mut result = 0;
+    for (i, update) in updates.iter().enumerate() {
+        let violations = rules.violations(update);
+        if violations.is_empty() {
+            result += update[update.len() / 2];
+        } else if explain {
+            println!("update {}:", i + 1);
+            for violation in violations {
+                println!("  {violation}");
             }
         }
-        result += values[values.len() / 2];
     }
 
     println!("result: {result}");
-
-    Ok(())
 }
@@ -1,43 +1,249 @@
 use std::collections::HashSet;
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::{self, BufRead, Write};
+use std::path::Path;
+
+use aoc2024::visualize::{Canvas, Color, VisualizeOptions, Visualizer};
+use image::{Rgb, RgbImage};
+
+// size in pixels of a single map cell in png route traces
+const TILE_SIZE: u32 = 7;
+
+// character used to mark the extra obstruction placed in part 2
+const OBSTRUCTION: char = 'O';
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum TraceFormat {
+    Text,
+    Png,
+}
 
 fn main() {
     let path = "inputs/day6";
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let visualize = VisualizeOptions::extract(&mut args);
+    let rules = GuardRules::from_args(&args);
 
     let map = parse_map(path);
 
-    println!("part 1 result: {:?}", solve_part1(&map));
-    println!("part 2 result: {:?}", solve_part2(&map));
+    if let Some(options) = visualize {
+        visualize_walk(&map, &rules, options).expect("couldn't visualize the guard's walk");
+        return;
+    }
+
+    // `--trace text` or `--trace png` writes the guard's routes under outputs/day6
+    let trace_format = args.iter().position(|a| a == "--trace").map(|i| {
+        match args.get(i + 1).map(|s| s.as_str()) {
+            Some("text") => TraceFormat::Text,
+            Some("png") => TraceFormat::Png,
+            _ => panic!("--trace needs a format, either 'text' or 'png'"),
+        }
+    });
+
+    println!("part 1 result: {:?}", solve_part1(&map, &rules));
+
+    let Some(format) = trace_format else {
+        println!("part 2 result: {:?}", solve_part2(&map, &rules));
+        return;
+    };
+    let obstructions = find_loop_obstructions(&map, &rules);
+    println!("part 2 result: {:?}", obstructions.len());
+
+    write_map(&trace_route(&map, &rules), "outputs/day6/route", format);
+    for (x, y) in obstructions.iter() {
+        let mut test_map = map.clone();
+        test_map[*y][*x] = OBSTRUCTION;
+        write_map(
+            &trace_route(&test_map, &rules),
+            &format!("outputs/day6/loops/{x}-{y}"),
+            format,
+        );
+    }
+    println!(
+        "routes written to outputs/day6/route and outputs/day6/loops ({} loops)",
+        obstructions.len()
+    );
 }
 
-fn solve_part1(map: &Vec<Vec<char>>) -> u64 {
-    let mut map = map.clone();
-    let initial_position = get_position(&map).unwrap();
-    let mut position = initial_position.clone();
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum TurnPolicy {
+    Right,
+    Left,
+    Reverse,
+}
 
-    while let Some(next_position) = position.get_next_on_map(&map) {
-        position = next_position;
-        set_marker_at_position(&mut map, &position, 'X');
+impl TurnPolicy {
+    fn apply(&self, direction: Direction) -> Direction {
+        match self {
+            TurnPolicy::Right => direction.turn_right(),
+            TurnPolicy::Left => direction.turn_right().turn_right().turn_right(),
+            TurnPolicy::Reverse => direction.turn_right().turn_right(),
+        }
+    }
+}
+
+// how guards move around the map
+#[derive(Clone, Debug)]
+struct GuardRules {
+    // characters that block a guard, on top of any placed obstruction
+    obstacles: Vec<char>,
+    turn: TurnPolicy,
+    // guards walking off one edge of the map come back in on the opposite edge
+    wrap: bool,
+}
+
+impl Default for GuardRules {
+    fn default() -> Self {
+        Self {
+            obstacles: vec!['#'],
+            turn: TurnPolicy::Right,
+            wrap: false,
+        }
     }
-    get_visited_positions(&map)
 }
 
-fn print_map_to_file(map: &Vec<Vec<char>>, filepath: &str) {
+impl GuardRules {
+    // e.g. `--turn left --wrap --obstacles #%`
+    fn from_args(args: &[String]) -> Self {
+        let mut rules = Self::default();
+        let value_of = |flag: &str| {
+            args.iter()
+                .position(|a| a == flag)
+                .map(|i| args.get(i + 1).map(|s| s.as_str()).unwrap_or(""))
+        };
+        if let Some(turn) = value_of("--turn") {
+            rules.turn = match turn {
+                "right" => TurnPolicy::Right,
+                "left" => TurnPolicy::Left,
+                "reverse" => TurnPolicy::Reverse,
+                _ => panic!("--turn needs one of 'right', 'left' or 'reverse'"),
+            };
+        }
+        if let Some(obstacles) = value_of("--obstacles") {
+            rules.obstacles = obstacles.chars().collect();
+        }
+        rules.wrap = args.iter().any(|a| a == "--wrap");
+        rules
+    }
+
+    fn is_obstacle(&self, marker: char) -> bool {
+        marker == OBSTRUCTION || self.obstacles.contains(&marker)
+    }
+
+    // the guard's next position, or None once it walks off the map. The guard keeps turning until
+    // the way ahead is clear, and stays where it is if it's boxed in on every side.
+    fn next_position(&self, map: &[Vec<char>], position: &Position) -> Option<Position> {
+        let mut direction = position.direction;
+        for _ in 0..4 {
+            let mut new_position = Position {
+                direction,
+                ..*position
+            }
+            .get_next();
+            if self.wrap {
+                new_position.x = new_position.x.rem_euclid(map[0].len() as i64);
+                new_position.y = new_position.y.rem_euclid(map.len() as i64);
+            } else if !position_within_map(map, &new_position) {
+                return None;
+            }
+            if !self.is_obstacle(get_marker_at_position(map, &new_position)) {
+                return Some(new_position);
+            }
+            direction = self.turn.apply(direction);
+        }
+        Some(*position)
+    }
+
+    // every position the guard passes through from `start`, and whether it ends up in a loop
+    fn walk(&self, map: &[Vec<char>], start: Position) -> (Vec<Position>, bool) {
+        let mut route = vec![start];
+        let mut visited = HashSet::from([start]);
+        let mut position = start;
+        while let Some(next_position) = self.next_position(map, &position) {
+            if !visited.insert(next_position) {
+                return (route, true);
+            }
+            route.push(next_position);
+            position = next_position;
+        }
+        (route, false)
+    }
+}
+
+fn solve_part1(map: &[Vec<char>], rules: &GuardRules) -> u64 {
+    let mut visited_positions = HashSet::new();
+    for guard in get_guards(map) {
+        let (route, _) = rules.walk(map, guard);
+        visited_positions.extend(route.iter().map(|p| (p.x, p.y)));
+    }
+    visited_positions.len() as u64
+}
+
+fn visualize_walk(
+    map: &[Vec<char>],
+    rules: &GuardRules,
+    options: VisualizeOptions,
+) -> io::Result<()> {
+    let mut map = map.to_vec();
+    let routes = get_guards(&map)
+        .into_iter()
+        .map(|guard| rules.walk(&map, guard).0)
+        .collect::<Vec<_>>();
+    let steps = routes.iter().map(|r| r.len()).max().unwrap_or(0);
+    let mut visualizer = Visualizer::new(options)?;
+
+    for i in 0..steps {
+        // guards that have already left the map (or finished a loop) stay where they last were
+        let guards = routes
+            .iter()
+            .map(|route| route[i.min(route.len() - 1)])
+            .collect::<Vec<_>>();
+        if !visualizer.show(&map_canvas(&map, &guards))? {
+            return Ok(());
+        }
+        for route in routes.iter() {
+            if i + 1 < route.len() {
+                mark_map(&mut map, &route[i], &route[i + 1]);
+            }
+        }
+    }
+    visualizer.finish()
+}
+
+fn map_canvas(map: &[Vec<char>], guards: &[Position]) -> Canvas {
+    let lines = map.iter().map(String::from_iter).collect::<Vec<_>>();
+    let mut canvas = Canvas::from_lines(&lines, |c| match c {
+        '|' | '-' | '+' => Some(Color::Yellow),
+        '.' => None,
+        _ => Some(Color::DarkGrey),
+    });
+    for guard in guards {
+        canvas.set(
+            guard.x as usize,
+            guard.y as usize,
+            guard.direction.marker(),
+            Some(Color::Red),
+        );
+    }
+    canvas
+}
+
+fn print_map_to_file(map: &[Vec<char>], filepath: &str) {
     // write the final map to an output file
     let mut file = File::create(filepath).expect("couldn't create file");
-    for line in map.into_iter().map(|x| String::from_iter(x)) {
+    for line in map.iter().map(String::from_iter) {
         writeln!(file, "{}", line).expect("couldn't write to file");
     }
 }
 
-fn mark_map(map: &mut Vec<Vec<char>>, position: &Position, next_position: &Position) {
-    let prev_marker = get_marker_at_position(&map, &position);
+fn mark_map(map: &mut [Vec<char>], position: &Position, next_position: &Position) {
+    let prev_marker = get_marker_at_position(map, position);
     let mut new_marker = match position.direction {
         Direction::North | Direction::South => '|',
         Direction::East | Direction::West => '-',
     };
     if position.direction != next_position.direction
+        || prev_marker == '+'
         || prev_marker == '|' && new_marker == '-'
         || prev_marker == '-' && new_marker == '|'
     {
@@ -46,62 +252,112 @@ fn mark_map(map: &mut Vec<Vec<char>>, position: &Position, next_position: &Posit
     set_marker_at_position(map, position, new_marker);
 }
 
-fn solve_part2(map: &Vec<Vec<char>>) -> u64 {
-    let map = map.clone();
-    let initial_position = get_position(&map).unwrap();
+// draws the guards' routes onto the map with `|`, `-` and `+`, following each guard until it
+// either leaves the map or starts going round in a loop
+fn trace_route(map: &[Vec<char>], rules: &GuardRules) -> Vec<Vec<char>> {
+    let mut map = map.to_vec();
+    let guards = get_guards(&map);
+    for guard in guards.iter() {
+        let (route, looped) = rules.walk(&map, *guard);
+        for pair in route.windows(2) {
+            mark_map(&mut map, &pair[0], &pair[1]);
+        }
+        let last = route[route.len() - 1];
+        match rules.next_position(&map, &last) {
+            Some(next_position) if looped => mark_map(&mut map, &last, &next_position),
+            _ => mark_map(&mut map, &last, &last),
+        }
+    }
+    for guard in guards.iter() {
+        set_marker_at_position(&mut map, guard, guard.direction.marker());
+    }
+    map
+}
 
-    // get all the positions that would be visited by the guard normally
-    let mut visited_without_obstacles: HashSet<Position> = HashSet::new();
-    let mut position = initial_position.clone();
-    visited_without_obstacles.insert(initial_position);
-    while let Some(next_position) = position.get_next_on_map(&map) {
-        visited_without_obstacles.insert(next_position);
-        position = next_position;
+fn write_map(map: &[Vec<char>], path: &str, format: TraceFormat) {
+    if let Some(parent) = Path::new(path).parent() {
+        fs::create_dir_all(parent).expect("couldn't create output directory");
+    }
+    match format {
+        TraceFormat::Text => print_map_to_file(map, &format!("{path}.txt")),
+        TraceFormat::Png => save_map_image(map, &format!("{path}.png")),
+    }
+}
+
+fn save_map_image(map: &[Vec<char>], filepath: &str) {
+    let mut img = RgbImage::new(
+        map[0].len() as u32 * TILE_SIZE,
+        map.len() as u32 * TILE_SIZE,
+    );
+    let mid = TILE_SIZE / 2;
+    for (y, row) in map.iter().enumerate() {
+        for (x, marker) in row.iter().enumerate() {
+            let color = match marker {
+                '#' => Rgb([128, 128, 128]),
+                'O' => Rgb([220, 50, 50]),
+                '^' | '>' | 'v' | '<' => Rgb([80, 200, 80]),
+                _ => Rgb([230, 200, 60]),
+            };
+            for dy in 0..TILE_SIZE {
+                for dx in 0..TILE_SIZE {
+                    let draw = match marker {
+                        '|' => dx == mid,
+                        '-' => dy == mid,
+                        '+' => dx == mid || dy == mid,
+                        '.' => false,
+                        _ => true,
+                    };
+                    if draw {
+                        img.put_pixel(x as u32 * TILE_SIZE + dx, y as u32 * TILE_SIZE + dy, color);
+                    }
+                }
+            }
+        }
     }
+    img.save(filepath).expect("couldn't save image");
+}
+
+fn solve_part2(map: &[Vec<char>], rules: &GuardRules) -> u64 {
+    find_loop_obstructions(map, rules).len() as u64
+}
 
-    let mut obstruction_positions = 0;
-    for y in 0..map.len() {
-        for x in 0..map[0].len() {
-            if initial_position.x as usize == x && initial_position.y as usize == y
-                || map[y][x] == '#'
+// positions (x, y) where placing an obstruction traps at least one guard in a loop
+fn find_loop_obstructions(map: &[Vec<char>], rules: &GuardRules) -> Vec<(usize, usize)> {
+    let guards = get_guards(map);
+
+    let mut obstruction_positions = Vec::new();
+    for (y, row) in map.iter().enumerate() {
+        for (x, marker) in row.iter().enumerate() {
+            if guards
+                .iter()
+                .any(|g| g.x as usize == x && g.y as usize == y)
+                || rules.is_obstacle(*marker)
             {
                 continue;
             }
-            let mut test_map = map.clone();
-            test_map[y][x] = 'O';
-
-            let mut p = initial_position;
-            let mut visited: HashSet<Position> = HashSet::new();
-
-            visited.insert(p);
-            while let Some(next_p) = p.get_next_on_map(&test_map) {
-                if visited.contains(&next_p) {
-                    obstruction_positions += 1;
-                    println!(
-                        "(x: {x}, y: {y}); obstruction_positions so far: {obstruction_positions}"
-                    );
-                    break;
-                }
-                p = next_p;
-                visited.insert(next_p);
+            let mut test_map = map.to_vec();
+            test_map[y][x] = OBSTRUCTION;
+
+            if guards.iter().any(|g| rules.walk(&test_map, *g).1) {
+                obstruction_positions.push((x, y));
             }
         }
     }
-    return obstruction_positions;
+    obstruction_positions
 }
 
-fn position_within_map(map: &Vec<Vec<char>>, position: &Position) -> bool {
+fn position_within_map(map: &[Vec<char>], position: &Position) -> bool {
     position.x >= 0
         && position.x < map[0].len() as i64
         && position.y >= 0
         && position.y < map.len() as i64
 }
 
-fn get_marker_at_position(map: &Vec<Vec<char>>, position: &Position) -> char {
+fn get_marker_at_position(map: &[Vec<char>], position: &Position) -> char {
     map[position.y as usize][position.x as usize]
 }
 
-fn set_marker_at_position(map: &mut Vec<Vec<char>>, position: &Position, c: char) {
+fn set_marker_at_position(map: &mut [Vec<char>], position: &Position, c: char) {
     map[position.y as usize][position.x as usize] = c;
 }
 
@@ -119,41 +375,25 @@ fn parse_map(path: &str) -> Vec<Vec<char>> {
     map
 }
 
-fn get_position(map: &Vec<Vec<char>>) -> Option<Position> {
-    let direction;
-    for i in 0..map.len() {
-        for j in 0..map[i].len() {
-            if "^V<>".contains(map[i][j]) {
-                if map[i][j] == '^' {
-                    direction = Direction::North;
-                } else if map[i][j] == '>' {
-                    direction = Direction::East;
-                } else if map[i][j] == 'V' {
-                    direction = Direction::South;
-                } else {
-                    direction = Direction::West;
-                }
-                return Some(Position {
-                    x: j as i64,
-                    y: i as i64,
-                    direction,
-                });
-            }
-        }
-    }
-    return None;
-}
-
-fn get_visited_positions(map: &Vec<Vec<char>>) -> u64 {
-    let mut visited_positions = 0;
-    for i in 0..map.len() {
-        for j in 0..map[i].len() {
-            if map[i][j] == 'X' || "^V<>".contains(map[i][j]) {
-                visited_positions += 1;
-            }
+fn get_guards(map: &[Vec<char>]) -> Vec<Position> {
+    let mut guards = Vec::new();
+    for (i, row) in map.iter().enumerate() {
+        for (j, marker) in row.iter().enumerate() {
+            let direction = match marker {
+                '^' => Direction::North,
+                '>' => Direction::East,
+                'v' => Direction::South,
+                '<' => Direction::West,
+                _ => continue,
+            };
+            guards.push(Position {
+                x: j as i64,
+                y: i as i64,
+                direction,
+            });
         }
     }
-    visited_positions
+    guards
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -165,7 +405,7 @@ struct Position {
 
 impl Position {
     fn get_next(&self) -> Self {
-        let mut new_position = self.clone();
+        let mut new_position = *self;
         match new_position.direction {
             Direction::North => new_position.y -= 1,
             Direction::East => new_position.x += 1,
@@ -174,22 +414,6 @@ impl Position {
         }
         new_position
     }
-
-    fn get_next_on_map(&self, map: &Vec<Vec<char>>) -> Option<Self> {
-        let mut new_position = self.get_next();
-
-        if position_within_map(map, &new_position) {
-            if "#O".contains(get_marker_at_position(map, &new_position)) {
-                new_position = Position {
-                    direction: self.direction.turn_right(),
-                    ..*self
-                }
-                .get_next();
-            }
-            return Some(new_position);
-        }
-        return None;
-    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -209,4 +433,154 @@ impl Direction {
             Direction::West => Direction::North,
         }
     }
+
+    fn marker(&self) -> char {
+        match self {
+            Direction::North => '^',
+            Direction::East => '>',
+            Direction::South => 'v',
+            Direction::West => '<',
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+....#.....
+.........#
+..........
+..#.......
+.......#..
+..........
+.#..^.....
+........#.
+#.........
+......#...";
+
+    fn map(text: &str) -> Vec<Vec<char>> {
+        text.lines().map(|line| line.chars().collect()).collect()
+    }
+
+    fn at(x: i64, y: i64, direction: Direction) -> Position {
+        Position { x, y, direction }
+    }
+
+    #[test]
+    fn example() {
+        let map = map(EXAMPLE);
+        let rules = GuardRules::default();
+        assert_eq!(solve_part1(&map, &rules), 41);
+        assert_eq!(solve_part2(&map, &rules), 6);
+    }
+
+    #[test]
+    fn turned_step_blocked_again() {
+        let rules = GuardRules::default();
+        let map = map(".#.\n.^#\n...");
+        let guard = get_guards(&map)[0];
+        assert_eq!(
+            rules.next_position(&map, &guard),
+            Some(at(1, 2, Direction::South))
+        );
+
+        // boxed in on every side, so the guard stays put
+        let map = self::map(".#.\n#^#\n.#.");
+        let guard = get_guards(&map)[0];
+        assert_eq!(rules.next_position(&map, &guard), Some(guard));
+    }
+
+    #[test]
+    fn south_facing_guard() {
+        let map = map("...\n.v.\n...");
+        let guards = get_guards(&map);
+        assert_eq!(guards, vec![at(1, 1, Direction::South)]);
+        let (route, looped) = GuardRules::default().walk(&map, guards[0]);
+        assert_eq!(route.last(), Some(&at(1, 2, Direction::South)));
+        assert!(!looped);
+    }
+
+    #[test]
+    fn turn_policies() {
+        let map = map("...\n.#.\n.^.");
+        let guard = get_guards(&map)[0];
+        let next = |turn| {
+            let rules = GuardRules {
+                turn,
+                ..GuardRules::default()
+            };
+            rules.next_position(&map, &guard)
+        };
+        assert_eq!(next(TurnPolicy::Right), Some(at(2, 2, Direction::East)));
+        assert_eq!(next(TurnPolicy::Left), Some(at(0, 2, Direction::West)));
+        // turning round walks straight off the bottom
+        assert_eq!(next(TurnPolicy::Reverse), None);
+    }
+
+    #[test]
+    fn wrapping() {
+        let map = map(".^.\n...\n.#.");
+        let guard = get_guards(&map)[0];
+        assert_eq!(GuardRules::default().next_position(&map, &guard), None);
+
+        let rules = GuardRules {
+            wrap: true,
+            ..GuardRules::default()
+        };
+        // comes back in at the bottom, where the obstacle turns it right
+        assert_eq!(
+            rules.next_position(&map, &guard),
+            Some(at(2, 0, Direction::East))
+        );
+        let (_, looped) = rules.walk(&map, guard);
+        assert!(looped);
+    }
+
+    #[test]
+    fn two_guards() {
+        let map = map("^....\n.....\n....<");
+        let guards = get_guards(&map);
+        assert_eq!(guards.len(), 2);
+        // one leaves straight away, the other walks the bottom row
+        assert_eq!(solve_part1(&map, &GuardRules::default()), 6);
+    }
+
+    #[test]
+    fn traced_route() {
+        let route = trace_route(&map(EXAMPLE), &GuardRules::default());
+        let expected = "\
+....#.....
+....+---+#
+....|...|.
+..#.|...|.
+..+-+-+#|.
+..|.|.|.|.
+.#+-^-+-+.
+.+----++#.
+#+----+|..
+......#|..";
+        assert_eq!(route, map(expected));
+    }
+
+    #[test]
+    fn traced_loop() {
+        // the first of the puzzle's six ways of trapping the guard
+        let mut lab = map(EXAMPLE);
+        lab[6][3] = OBSTRUCTION;
+        let route = trace_route(&lab, &GuardRules::default());
+        let expected = "\
+....#.....
+....+---+#
+....|...|.
+..#.|...|.
+....|..#|.
+....|...|.
+.#.O^---+.
+........#.
+#.........
+......#...";
+        assert_eq!(route, map(expected));
+    }
 }
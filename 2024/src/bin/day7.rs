@@ -1,6 +1,6 @@
-use std::fs::File;
-use std::io::{self, BufRead};
+use std::fs;
 
+use aoc2024::parse::{key_values, parse_lines};
 use itertools::{repeat_n, Itertools};
 
 fn main() {
@@ -19,7 +19,7 @@ fn main() {
     );
 }
 
-fn solve_part1(equations: &Vec<(u64, Vec<u64>)>) -> u64 {
+fn solve_part1(equations: &[(u64, Vec<u64>)]) -> u64 {
     let operators = ["+", "*"];
 
     let mut calibration_result = 0;
@@ -36,7 +36,7 @@ fn solve_part1(equations: &Vec<(u64, Vec<u64>)>) -> u64 {
     calibration_result
 }
 
-fn solve_part2(equations: &Vec<(u64, Vec<u64>)>) -> u64 {
+fn solve_part2(equations: &[(u64, Vec<u64>)]) -> u64 {
     let operators = ["+", "*", "||"];
     let mut calibration_result = 0;
     for eq in equations.iter() {
@@ -52,12 +52,12 @@ fn solve_part2(equations: &Vec<(u64, Vec<u64>)>) -> u64 {
     calibration_result
 }
 
-fn apply_operators(values: &Vec<u64>, operators: Vec<&&str>) -> Option<u64> {
+fn apply_operators(values: &[u64], operators: Vec<&&str>) -> Option<u64> {
     if values.len() - 1 != operators.len() {
         return None;
     }
 
-    let mut values = values.clone();
+    let mut values = values.to_vec();
     let mut operators = operators;
 
     while let Some(op) = operators.pop() {
@@ -76,21 +76,14 @@ fn apply_operators(values: &Vec<u64>, operators: Vec<&&str>) -> Option<u64> {
 }
 
 fn parse_input(path: &str) -> Vec<(u64, Vec<u64>)> {
-    let file = File::open(path).expect("unable to read input file");
-    let reader = io::BufReader::new(file);
+    let input = fs::read_to_string(path).expect("unable to read input file");
 
-    reader
-        .lines()
-        .map(|line| {
-            let line = line.expect("couldn't read line");
-            let eq = line.split(':').collect::<Vec<_>>();
-            let lhs = eq[0].parse::<u64>().expect("couldn't parse numbers");
-            let rhs = eq[1]
-                .split_whitespace()
-                .map(|x| x.parse::<u64>().expect("couldn't parse numbers"))
-                .rev()
-                .collect::<Vec<_>>();
-            return (lhs, rhs);
+    parse_lines(&input, 1, key_values())
+        .unwrap_or_else(|e| panic!("couldn't parse equation at {e}"))
+        .into_iter()
+        .map(|(lhs, mut rhs): (u64, Vec<u64>)| {
+            rhs.reverse();
+            (lhs, rhs)
         })
-        .collect::<Vec<_>>()
+        .collect()
 }
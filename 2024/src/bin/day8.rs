@@ -1,93 +1,349 @@
 use std::{
     collections::{HashMap, HashSet},
     fs::File,
+    hash::Hash,
     io::{self, BufRead},
+    ops::RangeInclusive,
 };
 
+use aoc2024::parse;
 use itertools::Itertools;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 struct Point(i32, i32);
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct Point3(i32, i32, i32);
+
+// positions and offsets on a map, which covers every position from the origin up to (but not
+// including) its size along each axis
+trait Vector: Copy + Eq + Hash {
+    fn add(self, other: Self) -> Self;
+    fn sub(self, other: Self) -> Self;
+    fn scale(self, k: i32) -> Self;
+    // the smallest whole step in the same direction
+    fn reduce(self) -> Self;
+    fn is_in_map(&self, size: &Self) -> bool;
+}
+
+impl Vector for Point {
+    fn add(self, other: Self) -> Self {
+        Point(self.0 + other.0, self.1 + other.1)
+    }
+
+    fn sub(self, other: Self) -> Self {
+        Point(self.0 - other.0, self.1 - other.1)
+    }
+
+    fn scale(self, k: i32) -> Self {
+        Point(self.0 * k, self.1 * k)
+    }
+
+    fn reduce(self) -> Self {
+        let divisor = gcd(self.0, self.1);
+        Point(self.0 / divisor, self.1 / divisor)
+    }
+
+    fn is_in_map(&self, size: &Self) -> bool {
+        self.0 >= 0 && self.0 < size.0 && self.1 >= 0 && self.1 < size.1
+    }
+}
+
+impl Vector for Point3 {
+    fn add(self, other: Self) -> Self {
+        Point3(self.0 + other.0, self.1 + other.1, self.2 + other.2)
+    }
+
+    fn sub(self, other: Self) -> Self {
+        Point3(self.0 - other.0, self.1 - other.1, self.2 - other.2)
+    }
+
+    fn scale(self, k: i32) -> Self {
+        Point3(self.0 * k, self.1 * k, self.2 * k)
+    }
+
+    fn reduce(self) -> Self {
+        let divisor = gcd(gcd(self.0, self.1), self.2);
+        Point3(self.0 / divisor, self.1 / divisor, self.2 / divisor)
+    }
+
+    fn is_in_map(&self, size: &Self) -> bool {
+        self.0 >= 0
+            && self.0 < size.0
+            && self.1 >= 0
+            && self.1 < size.1
+            && self.2 >= 0
+            && self.2 < size.2
+    }
+}
+
+// where a pair of antennas of the same frequency creates antinodes
+trait AntinodeModel<P: Vector> {
+    // the antinodes of `a` and `b` that lie on the map
+    fn antinodes(&self, a: P, b: P, size: P) -> Vec<P>;
+}
+
+// one antinode beyond each antenna, as far from it as the antennas are from each other
+struct Reflection;
+
+impl<P: Vector> AntinodeModel<P> for Reflection {
+    fn antinodes(&self, a: P, b: P, size: P) -> Vec<P> {
+        let diff = a.sub(b);
+        [a.add(diff), b.sub(diff)]
+            .into_iter()
+            .filter(|p| p.is_in_map(&size))
+            .collect()
+    }
+}
+
+// an antinode at every grid position in line with the antennas
+struct Resonance;
+
+impl<P: Vector> AntinodeModel<P> for Resonance {
+    fn antinodes(&self, a: P, b: P, size: P) -> Vec<P> {
+        let mut antinodes = Vec::new();
+
+        let diff = a.sub(b).reduce();
+
+        let mut point = a;
+        while point.is_in_map(&size) {
+            antinodes.push(point);
+            point = point.add(diff);
+        }
+
+        let mut point = a;
+        while point.is_in_map(&size) {
+            antinodes.push(point);
+            point = point.sub(diff);
+        }
+        antinodes
+    }
+}
+
+// an antinode k times the antennas' distance beyond each antenna, for each k in `range`.
+// `1..=1` is the same as `Reflection`. `0..` is only the same as `Resonance` when there are no
+// grid points in line between the antennas.
+struct Harmonics {
+    range: RangeInclusive<u32>,
+}
+
+impl<P: Vector> AntinodeModel<P> for Harmonics {
+    fn antinodes(&self, a: P, b: P, size: P) -> Vec<P> {
+        let diff = a.sub(b);
+        let mut antinodes = Vec::new();
+        for (antenna, direction) in [(a, diff), (b, diff.scale(-1))] {
+            // the antenna is on the map, so once the antinodes leave it they don't come back
+            for k in self.range.clone() {
+                let point = antenna.add(direction.scale(k as i32));
+                if !point.is_in_map(&size) {
+                    break;
+                }
+                antinodes.push(point);
+            }
+        }
+        antinodes
+    }
+}
+
+// the antinodes one frequency's antennas create, and how many of them other frequencies
+// create as well
+struct FrequencyReport<P> {
+    frequency: char,
+    antennas: usize,
+    antinodes: HashSet<P>,
+    overlaps: Vec<(char, usize)>,
+}
+
 fn main() {
     let path = "inputs/day8";
 
-    let map = parse_map(&path);
-    let map_width = map[0].len() as i32;
-    let map_height = map.len() as i32;
+    // `--harmonics <k>|<a>..<b>|<a>..=<b>|<a>..` counts antinodes k times the antennas'
+    // distance away for every k in the range, and `--3d` reads the input as layers of a
+    // 3D map separated by blank lines
+    // `--report` breaks the antinodes down by frequency and draws them over the map, `--map`
+    // only draws them. Both use part 1's model unless given `--part2` or `--harmonics`.
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let report = args.iter().any(|a| a == "--report");
+
+    if args.iter().any(|a| a == "--3d") {
+        let layers = parse_layers(path);
+        let size = Point3(
+            layers[0][0].len() as i32,
+            layers[0].len() as i32,
+            layers.len() as i32,
+        );
+        let antennas = find_antennas_3d(&layers);
+        if report {
+            print_report(&frequency_reports(
+                &antennas,
+                size,
+                model_from_args(&args).as_ref(),
+            ));
+            return;
+        }
+        print_solutions(&antennas, size, harmonics_from_args(&args));
+        return;
+    }
+
+    let map = parse_map(path);
+    let size = Point(map[0].len() as i32, map.len() as i32);
     let antennas = find_antennas(&map);
 
+    if report || args.iter().any(|a| a == "--map") {
+        let model = model_from_args(&args);
+        if report {
+            print_report(&frequency_reports(&antennas, size, model.as_ref()));
+            println!();
+        }
+        let antinodes = find_antinodes(&antennas, size, model.as_ref());
+        print!("{}", draw_antinodes(&map, &antinodes));
+        return;
+    }
+
+    print_solutions(&antennas, size, harmonics_from_args(&args));
+}
+
+fn harmonics_from_args(args: &[String]) -> Option<Harmonics> {
+    args.iter().position(|a| a == "--harmonics").map(|i| {
+        let range = args.get(i + 1).expect("--harmonics needs a range");
+        Harmonics {
+            range: parse::range(range)
+                .unwrap_or_else(|| panic!("invalid harmonics range '{range}'")),
+        }
+    })
+}
+
+fn model_from_args<P: Vector>(args: &[String]) -> Box<dyn AntinodeModel<P>> {
+    if let Some(harmonics) = harmonics_from_args(args) {
+        Box::new(harmonics)
+    } else if args.iter().any(|a| a == "--part2") {
+        Box::new(Resonance)
+    } else {
+        Box::new(Reflection)
+    }
+}
+
+fn print_solutions<P: Vector>(
+    antennas: &HashMap<char, Vec<P>>,
+    size: P,
+    harmonics: Option<Harmonics>,
+) {
+    if let Some(harmonics) = harmonics {
+        println!(
+            "harmonics {:?}: unique antinode locations: {:?}",
+            harmonics.range,
+            find_antinodes(antennas, size, &harmonics).len()
+        );
+        return;
+    }
+
     println!(
         "part 1: unique antinode locations: {:?}",
-        solve_part1(&antennas, map_width, map_height)
+        solve_part1(antennas, size).len()
     );
     println!(
         "part 2: unique antinode locations: {:?}",
-        solve_part2(&antennas, map_width, map_height)
+        solve_part2(antennas, size).len()
     );
 }
 
-fn solve_part1(antennas: &HashMap<char, Vec<Point>>, map_width: i32, map_height: i32) -> u64 {
-    let mut antinodes: HashSet<Point> = HashSet::new();
+fn find_antinodes<P: Vector>(
+    antennas: &HashMap<char, Vec<P>>,
+    size: P,
+    model: &dyn AntinodeModel<P>,
+) -> HashSet<P> {
+    let mut antinodes: HashSet<P> = HashSet::new();
 
-    for (_antenna, locations) in antennas.iter() {
+    for locations in antennas.values() {
         for pair in locations.iter().combinations(2) {
-            get_antinodes((pair[0], pair[1])).iter().for_each(|p| {
-                if p.0 >= 0 && p.0 < map_width && p.1 >= 0 && p.1 < map_height {
-                    antinodes.insert(*p);
-                }
-            });
+            antinodes.extend(model.antinodes(*pair[0], *pair[1], size));
         }
     }
-    antinodes.len() as u64
-}
-
-fn get_antinodes(pair: (&Point, &Point)) -> Vec<Point> {
-    let (a, b) = pair;
-    let diff = (a.0 - b.0, a.1 - b.1);
-    vec![
-        Point(a.0 + diff.0, a.1 + diff.1),
-        Point(b.0 - diff.0, b.1 - diff.1),
-    ]
+    antinodes
 }
 
-fn solve_part2(antennas: &HashMap<char, Vec<Point>>, map_width: i32, map_height: i32) -> u64 {
-    let mut antinodes: HashSet<Point> = HashSet::new();
+fn frequency_reports<P: Vector>(
+    antennas: &HashMap<char, Vec<P>>,
+    size: P,
+    model: &dyn AntinodeModel<P>,
+) -> Vec<FrequencyReport<P>> {
+    let mut reports: Vec<FrequencyReport<P>> = antennas
+        .iter()
+        .map(|(frequency, locations)| FrequencyReport {
+            frequency: *frequency,
+            antennas: locations.len(),
+            antinodes: find_antinodes(
+                &HashMap::from([(*frequency, locations.clone())]),
+                size,
+                model,
+            ),
+            overlaps: Vec::new(),
+        })
+        .collect();
+    reports.sort_by_key(|report| report.frequency);
 
-    for (_antenna, locations) in antennas.iter() {
-        for pair in locations.iter().combinations(2) {
-            get_antinodes_using_updated_model((pair[0], pair[1]), map_width, map_height)
-                .iter()
-                .for_each(|p| {
-                    antinodes.insert(*p);
-                });
-        }
+    for i in 0..reports.len() {
+        let overlaps = reports
+            .iter()
+            .filter(|other| other.frequency != reports[i].frequency)
+            .map(|other| {
+                let shared = reports[i].antinodes.intersection(&other.antinodes).count();
+                (other.frequency, shared)
+            })
+            .filter(|(_, shared)| *shared > 0)
+            .collect();
+        reports[i].overlaps = overlaps;
     }
-    antinodes.len() as u64
+    reports
 }
 
-fn get_antinodes_using_updated_model(
-    pair: (&Point, &Point),
-    map_width: i32,
-    map_height: i32,
-) -> Vec<Point> {
-    let (a, b) = pair;
-    let mut antinodes = Vec::new();
+fn print_report<P>(reports: &[FrequencyReport<P>]) {
+    for report in reports {
+        let overlaps = report
+            .overlaps
+            .iter()
+            .map(|(frequency, shared)| format!("{shared} with {frequency}"))
+            .join(", ");
+        println!(
+            "{}: {} antennas, {} antinodes{}",
+            report.frequency,
+            report.antennas,
+            report.antinodes.len(),
+            if overlaps.is_empty() {
+                String::new()
+            } else {
+                format!(", shared: {overlaps}")
+            }
+        );
+    }
+}
 
-    let diff = reduce((a.0 - b.0, a.1 - b.1));
+// the map with `#` on every empty cell with an antinode, the way the puzzle draws them
+fn draw_antinodes(map: &[Vec<char>], antinodes: &HashSet<Point>) -> String {
+    map.iter()
+        .enumerate()
+        .map(|(y, row)| {
+            row.iter()
+                .enumerate()
+                .map(|(x, c)| {
+                    if *c == '.' && antinodes.contains(&Point(x as i32, y as i32)) {
+                        '#'
+                    } else {
+                        *c
+                    }
+                })
+                .collect::<String>()
+                + "\n"
+        })
+        .collect()
+}
 
-    let mut point = *a;
-    while point.0 >= 0 && point.0 < map_width && point.1 >= 0 && point.1 < map_height {
-        antinodes.push(point);
-        point = Point(point.0 + diff.0, point.1 + diff.1);
-    }
+fn solve_part1<P: Vector>(antennas: &HashMap<char, Vec<P>>, size: P) -> HashSet<P> {
+    find_antinodes(antennas, size, &Reflection)
+}
 
-    let mut point = *a;
-    while point.0 >= 0 && point.0 < map_width && point.1 >= 0 && point.1 < map_height {
-        antinodes.push(point);
-        point = Point(point.0 - diff.0, point.1 - diff.1);
-    }
-    antinodes
+fn solve_part2<P: Vector>(antennas: &HashMap<char, Vec<P>>, size: P) -> HashSet<P> {
+    find_antinodes(antennas, size, &Resonance)
 }
 
 fn gcd(a: i32, b: i32) -> i32 {
@@ -98,29 +354,36 @@ fn gcd(a: i32, b: i32) -> i32 {
     }
 }
 
-fn reduce(pair: (i32, i32)) -> (i32, i32) {
-    let divisor = gcd(pair.0, pair.1);
-    (pair.0 / divisor, pair.1 / divisor)
-}
-
-fn find_antennas(map: &Vec<Vec<char>>) -> HashMap<char, Vec<Point>> {
-    let mut antennas = HashMap::new();
-    for y in 0..map.len() {
-        for x in 0..map[0].len() {
-            if map[y][x] != '.' {
-                let point = Point(x as i32, y as i32);
-                antennas
-                    .entry(map[y][x])
-                    .and_modify(|z: &mut Vec<Point>| z.push(point))
-                    .or_insert(vec![point]);
-            }
+fn group_antennas<P>(cells: impl IntoIterator<Item = (char, P)>) -> HashMap<char, Vec<P>> {
+    let mut antennas: HashMap<char, Vec<P>> = HashMap::new();
+    for (frequency, point) in cells {
+        if frequency != '.' {
+            antennas.entry(frequency).or_default().push(point);
         }
     }
     antennas
 }
 
+fn find_antennas(map: &[Vec<char>]) -> HashMap<char, Vec<Point>> {
+    group_antennas(map.iter().enumerate().flat_map(|(y, row)| {
+        row.iter()
+            .enumerate()
+            .map(move |(x, c)| (*c, Point(x as i32, y as i32)))
+    }))
+}
+
+fn find_antennas_3d(layers: &[Vec<Vec<char>>]) -> HashMap<char, Vec<Point3>> {
+    group_antennas(layers.iter().enumerate().flat_map(|(z, map)| {
+        map.iter().enumerate().flat_map(move |(y, row)| {
+            row.iter()
+                .enumerate()
+                .map(move |(x, c)| (*c, Point3(x as i32, y as i32, z as i32)))
+        })
+    }))
+}
+
 fn parse_map(path: &str) -> Vec<Vec<char>> {
-    let file = File::open(path).expect(&format!("unable to open file {path}"));
+    let file = File::open(path).unwrap_or_else(|_| panic!("unable to open file {path}"));
     let reader = io::BufReader::new(file);
 
     reader
@@ -128,3 +391,173 @@ fn parse_map(path: &str) -> Vec<Vec<char>> {
         .map(|line| line.expect("couldn't parse line").chars().collect())
         .collect()
 }
+
+// a 3D map, written as one 2D map per layer with blank lines between them
+fn parse_layers(path: &str) -> Vec<Vec<Vec<char>>> {
+    let map = parse_map(path);
+    let layers: Vec<Vec<Vec<char>>> = map
+        .split(|row| row.is_empty())
+        .filter(|layer| !layer.is_empty())
+        .map(|layer| layer.to_vec())
+        .collect();
+    let (width, height) = (layers[0][0].len(), layers[0].len());
+    assert!(
+        layers
+            .iter()
+            .all(|layer| layer.len() == height && layer.iter().all(|row| row.len() == width)),
+        "all layers of a 3D map must be the same size"
+    );
+    layers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+............
+........0...
+.....0......
+.......0....
+....0.......
+......A.....
+............
+............
+........A...
+.........A..
+............
+............";
+
+    fn example() -> (Vec<Vec<char>>, HashMap<char, Vec<Point>>, Point) {
+        let map = EXAMPLE
+            .lines()
+            .map(|line| line.chars().collect())
+            .collect::<Vec<Vec<char>>>();
+        let antennas = find_antennas(&map);
+        let size = Point(map[0].len() as i32, map.len() as i32);
+        (map, antennas, size)
+    }
+
+    fn harmonics(range: &str) -> Harmonics {
+        harmonics_from_args(&["--harmonics".to_string(), range.to_string()]).unwrap()
+    }
+
+    #[test]
+    fn models() {
+        let (_, antennas, size) = example();
+        assert_eq!(find_antinodes(&antennas, size, &Reflection).len(), 14);
+        assert_eq!(find_antinodes(&antennas, size, &Resonance).len(), 34);
+        assert_eq!(
+            find_antinodes(&antennas, size, &harmonics("1")),
+            solve_part1(&antennas, size)
+        );
+        assert_eq!(
+            find_antinodes(&antennas, size, &harmonics("0..")),
+            solve_part2(&antennas, size)
+        );
+        // the part 1 antinodes, and those twice as far out
+        let doubled = find_antinodes(&antennas, size, &harmonics("1..3"));
+        assert!(doubled.is_superset(&solve_part1(&antennas, size)));
+        assert!(doubled.contains(&Point(11, 11)));
+    }
+
+    #[test]
+    fn drawn_maps() {
+        let (map, antennas, size) = example();
+        let part1 = "\
+......#....#
+...#....0...
+....#0....#.
+..#....0....
+....0....#..
+.#....A.....
+...#........
+#......#....
+........A...
+.........A..
+..........#.
+..........#.
+";
+        let part2 = "\
+##....#....#
+.#.#....0...
+..#.#0....#.
+..##...0....
+....0....#..
+.#...#A....#
+...#..#.....
+#....#.#....
+..#.....A...
+....#....A..
+.#........#.
+...#......##
+";
+        assert_eq!(draw_antinodes(&map, &solve_part1(&antennas, size)), part1);
+        assert_eq!(draw_antinodes(&map, &solve_part2(&antennas, size)), part2);
+    }
+
+    #[test]
+    fn overlaps() {
+        let (_, antennas, size) = example();
+        let reports = frequency_reports(&antennas, size, &Reflection);
+        let summary = reports
+            .iter()
+            .map(|r| {
+                (
+                    r.frequency,
+                    r.antennas,
+                    r.antinodes.len(),
+                    r.overlaps.clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+        // both frequencies have an antinode at (3, 1)
+        assert_eq!(
+            summary,
+            [('0', 4, 10, vec![('A', 1)]), ('A', 3, 5, vec![('0', 1)])]
+        );
+    }
+
+    #[test]
+    fn harmonics_step_by_whole_distances() {
+        let (a, b, size) = (Point(0, 0), Point(2, 4), Point(5, 5));
+        assert_eq!(harmonics("0..").antinodes(a, b, size), [a, b]);
+        let resonance: HashSet<_> = Resonance.antinodes(a, b, size).into_iter().collect();
+        assert_eq!(resonance, HashSet::from([a, Point(1, 2), b]));
+    }
+
+    #[test]
+    fn harmonics_ranges() {
+        assert_eq!(harmonics("2..").range, 2..=u32::MAX);
+        assert_eq!(harmonics("1..3").range, 1..=2);
+        assert_eq!(harmonics("1..=3").range, 1..=3);
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid harmonics range '3..0'")]
+    fn empty_harmonics_range() {
+        harmonics("3..0");
+    }
+
+    #[test]
+    fn points_in_3d() {
+        let size = Point3(5, 5, 5);
+        let (a, b) = (Point3(1, 1, 1), Point3(2, 2, 2));
+        assert_eq!(
+            Reflection.antinodes(a, b, size),
+            [Point3(0, 0, 0), Point3(3, 3, 3)]
+        );
+        // the antennas are two whole steps of (1, 2, 1) apart
+        let b = Point3(3, 5, 3);
+        let size = Point3(6, 6, 6);
+        assert_eq!(
+            Resonance.antinodes(Point3(1, 1, 1), b, size),
+            [
+                Point3(1, 1, 1),
+                Point3(1, 1, 1),
+                Point3(2, 3, 2),
+                Point3(3, 5, 3)
+            ]
+        );
+    }
+}
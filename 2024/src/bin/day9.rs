@@ -1,8 +1,50 @@
-use std::{fs::File, io::Read};
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+    fs::File,
+    io::{self, Read},
+};
+
+use aoc2024::visualize::{Canvas, Color, VisualizeOptions, Visualizer};
+use itertools::Itertools;
+
+// blocks per row when visualizing the disk
+const VISUALIZE_WIDTH: usize = 64;
 
 fn main() {
     let input_path = "inputs/day9";
-    let diskmap = parse_diskmap(&input_path);
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let visualize = VisualizeOptions::extract(&mut args);
+
+    let diskmap = parse_diskmap(input_path);
+
+    if let Some(options) = visualize {
+        let whole_files = args.iter().any(|a| a == "--part2");
+        visualize_compaction(&diskmap, whole_files, options)
+            .expect("couldn't visualize compaction");
+        return;
+    }
+
+    // `--inspect [--part1|--part2] [--layout base36|delimited]` prints the disk's layout,
+    // optionally after compacting it
+    if args.iter().any(|a| a == "--inspect") {
+        let mut disk = DiskMap::from_diskmap(&diskmap);
+        if args.iter().any(|a| a == "--part1") {
+            disk.compact_fragmenting(|_, _| {});
+        } else if args.iter().any(|a| a == "--part2") {
+            disk.compact_whole_files(|_, _| {});
+        }
+        let style = match args.iter().position(|a| a == "--layout") {
+            None => LayoutStyle::Base36,
+            Some(i) => match args.get(i + 1).map(|s| s.as_str()) {
+                Some("base36") => LayoutStyle::Base36,
+                Some("delimited") => LayoutStyle::Delimited(','),
+                _ => panic!("--layout needs one of 'base36' or 'delimited'"),
+            },
+        };
+        inspect(&disk, style);
+        return;
+    }
 
     println!("part1: filesystem checksum: {:?}", solve_part1(&diskmap));
     println!("part2: filesystem checksum: {:?}", solve_part2(&diskmap));
@@ -13,7 +55,7 @@ fn main() {
 }
 
 fn parse_diskmap(path: &str) -> Vec<u8> {
-    let mut file = File::open(path).expect(&format!("couldn't open file '{path}'"));
+    let mut file = File::open(path).unwrap_or_else(|_| panic!("couldn't open file '{path}'"));
     let mut buf = String::new();
     file.read_to_string(&mut buf).expect("problem reading file");
 
@@ -22,94 +64,283 @@ fn parse_diskmap(path: &str) -> Vec<u8> {
         .collect()
 }
 
-fn solve_part1(diskmap: &Vec<u8>) -> u64 {
-    let mut blocks: Vec<Option<u64>> = diskmap
-        .iter()
-        .enumerate()
-        .map(|(idx, val)| {
+// a run of consecutive blocks on the disk
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Span {
+    start: usize,
+    len: usize,
+}
+
+// a (part of a) file being moved during compaction
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Move {
+    file: u64,
+    from: usize,
+    to: usize,
+    len: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum LayoutStyle {
+    // every block as a fixed width base-36 file id, so ids of any size line up
+    Base36,
+    // every block as a decimal file id, separated by the given character
+    Delimited(char),
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct FragmentationStats {
+    files: usize,
+    fragmented_files: usize,
+    fragments: usize,
+    free_blocks: usize,
+    free_spans: usize,
+    largest_free_span: usize,
+}
+
+#[derive(Clone, Debug)]
+struct DiskMap {
+    size: usize,
+    // spans occupied by each file, indexed by file id. The first span is where the file
+    // started out, any others are fragments moved there during compaction.
+    files: Vec<Vec<Span>>,
+    // every free span, indexed by the span's length. Spans of 9 blocks or more, which fit
+    // any file, share the last heap.
+    free: [BinaryHeap<Reverse<Span>>; 10],
+}
+
+impl DiskMap {
+    fn from_diskmap(diskmap: &[u8]) -> Self {
+        let mut files = Vec::new();
+        let mut gaps: Vec<Span> = Vec::new();
+        let mut position = 0;
+        for (idx, val) in diskmap.iter().enumerate() {
+            let len = *val as usize;
             if idx % 2 == 0 {
-                vec![Some(idx as u64 / 2); *val as usize]
-            } else {
-                vec![None; *val as usize]
+                files.push(vec![Span {
+                    start: position,
+                    len,
+                }]);
+            } else if len > 0 {
+                match gaps.last_mut() {
+                    // only an empty file lies between them, so it's all one free span
+                    Some(gap) if gap.start + gap.len == position => gap.len += len,
+                    _ => gaps.push(Span {
+                        start: position,
+                        len,
+                    }),
+                }
             }
-        })
-        .flatten()
-        .collect();
-    let mut i = 0;
-    while i < blocks.len() {
-        if blocks[i].is_none() {
+            position += len;
+        }
+        let mut disk = Self {
+            size: position,
+            files,
+            free: Default::default(),
+        };
+        for gap in gaps {
+            disk.add_free(gap);
+        }
+        disk
+    }
+
+    // the inverse of `from_diskmap`. Only layouts where files are whole, in id order, with no
+    // more than 9 blocks in any file or gap can be written in the dense format.
+    fn to_diskmap(&self) -> Option<Vec<u8>> {
+        let whole = (0..self.files.len())
+            .map(|id| match self.file_spans(id as u64).as_slice() {
+                [] => Some(None),
+                [span] => Some(Some(*span)),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let mut diskmap = Vec::new();
+        let mut position = 0;
+        for id in 0..self.files.len() {
+            let span = whole[id].unwrap_or_else(|| {
+                // empty files stay where they started out, unless the files either side of
+                // them have moved past that
+                let next = whole[id + 1..]
+                    .iter()
+                    .flatten()
+                    .map(|span| span.start)
+                    .next()
+                    .unwrap_or(self.size);
+                Span {
+                    start: self.files[id][0].start.clamp(position, next.max(position)),
+                    len: 0,
+                }
+            });
+            if span.start < position || (id == 0 && span.start > 0) {
+                return None;
+            }
+            if id > 0 {
+                diskmap.push(span.start - position);
+            }
+            diskmap.push(span.len);
+            position = span.start + span.len;
+        }
+        if self.size > position {
+            diskmap.push(self.size - position);
+        }
+        diskmap
+            .into_iter()
+            .map(|len| u8::try_from(len).ok().filter(|len| *len <= 9))
+            .collect()
+    }
+
+    // the blocks a file occupies, in disk order with adjacent fragments merged together
+    fn file_spans(&self, id: u64) -> Vec<Span> {
+        let mut spans = self.files[id as usize]
+            .iter()
+            .filter(|span| span.len > 0)
+            .copied()
+            .collect::<Vec<_>>();
+        spans.sort();
+        let mut merged: Vec<Span> = Vec::new();
+        for span in spans {
+            match merged.last_mut() {
+                Some(last) if last.start + last.len == span.start => last.len += span.len,
+                _ => merged.push(span),
+            }
+        }
+        merged
+    }
+
+    fn fragmentation(&self) -> FragmentationStats {
+        let mut stats = FragmentationStats {
+            files: self.files.len(),
+            ..Default::default()
+        };
+        for id in 0..self.files.len() {
+            let fragments = self.file_spans(id as u64).len();
+            stats.fragments += fragments;
+            if fragments > 1 {
+                stats.fragmented_files += 1;
+            }
+        }
+        let blocks = self.blocks();
+        for (is_free, run) in &blocks.iter().chunk_by(|block| block.is_none()) {
+            if is_free {
+                let len = run.count();
+                stats.free_blocks += len;
+                stats.free_spans += 1;
+                stats.largest_free_span = stats.largest_free_span.max(len);
+            }
+        }
+        stats
+    }
+
+    // the leftmost free span at least `min_len` blocks long that starts before `before`,
+    // as (start, len)
+    fn leftmost_free(&self, min_len: usize, before: usize) -> Option<(usize, usize)> {
+        (min_len.clamp(1, 9)..self.free.len())
+            .filter_map(|len| self.free[len].peek().map(|Reverse(span)| *span))
+            .filter(|span| span.start < before && span.len >= min_len)
+            .min()
+            .map(|span| (span.start, span.len))
+    }
+
+    fn add_free(&mut self, span: Span) {
+        self.free[span.len.min(9)].push(Reverse(span));
+    }
+
+    fn take_free(&mut self, start: usize, len: usize, used: usize) {
+        self.free[len.min(9)].pop();
+        if len > used {
+            self.add_free(Span {
+                start: start + used,
+                len: len - used,
+            });
+        }
+    }
+
+    // moves file blocks from the end of the disk into the leftmost free blocks, splitting
+    // files up where necessary, until there are no gaps left between files
+    fn compact_fragmenting(&mut self, mut on_move: impl FnMut(&Self, Move)) {
+        for id in (0..self.files.len()).rev() {
             loop {
-                if let Some(v) = blocks.pop().unwrap() {
-                    blocks[i] = Some(v);
-                    break;
-                } else if i >= blocks.len() {
+                let original = self.files[id][0];
+                if original.len == 0 {
                     break;
                 }
+                let Some((start, len)) = self.leftmost_free(1, original.start) else {
+                    return;
+                };
+                let moved = len.min(original.len);
+                self.take_free(start, len, moved);
+                self.files[id][0].len -= moved;
+                self.files[id].push(Span { start, len: moved });
+                let event = Move {
+                    file: id as u64,
+                    from: original.start + original.len - moved,
+                    to: start,
+                    len: moved,
+                };
+                on_move(self, event);
             }
         }
-        i += 1;
     }
-    blocks
-        .iter()
-        .enumerate()
-        .map(|(idx, val)| idx as u64 * val.unwrap())
-        .sum()
-}
 
-fn solve_part2(diskmap: &Vec<u8>) -> u64 {
-    let mut blocks: Vec<Vec<Option<u64>>> = diskmap
-        .iter()
-        .enumerate()
-        .filter_map(|(idx, val)| {
-            let block_size = *val as usize;
-            if block_size < 1 {
-                return None;
+    // moves each whole file, highest id first, into the leftmost free span that fits it
+    fn compact_whole_files(&mut self, mut on_move: impl FnMut(&Self, Move)) {
+        for id in (0..self.files.len()).rev() {
+            let file = self.files[id][0];
+            if file.len == 0 {
+                continue;
             }
-            let block = if idx % 2 == 0 {
-                Some((idx / 2) as u64)
-            } else {
-                None
+            let Some((start, len)) = self.leftmost_free(file.len, file.start) else {
+                continue;
             };
-            Some(vec![block; block_size])
-        })
-        .collect();
+            self.take_free(start, len, file.len);
+            self.files[id][0].start = start;
+            let event = Move {
+                file: id as u64,
+                from: file.start,
+                to: start,
+                len: file.len,
+            };
+            on_move(self, event);
+        }
+    }
 
-    let mut i = blocks.len() - 1;
-    loop {
-        if blocks[i][0].is_some() {
-            for j in 0..i {
-                if blocks[j][0].is_none() {
-                    let free_space = blocks[j].len();
-                    let file_size = blocks[i].len();
-                    if free_space >= file_size {
-                        blocks.swap(j, i);
-                        if free_space > file_size {
-                            // resize the free block to match the file block
-                            blocks[i].resize(file_size, None);
-                            // insert free block with the remaining free space after the file block
-                            blocks.insert(j + 1, vec![None; free_space - file_size]);
-                            // update iteration index to reflect the added block
-                            i += 1;
-                        }
-                        break;
-                    }
-                }
+    fn blocks(&self) -> Vec<Option<u64>> {
+        let mut blocks = vec![None; self.size];
+        for (id, spans) in self.files.iter().enumerate() {
+            for span in spans {
+                blocks[span.start..span.start + span.len].fill(Some(id as u64));
             }
         }
-        i -= 1;
-        if i == 0 {
-            break;
-        }
+        blocks
     }
-    blocks
-        .iter()
-        .flatten()
-        .enumerate()
-        .filter_map(|(idx, val)| val.map(|v| v * idx as u64))
-        .sum()
+
+    fn checksum(&self) -> u64 {
+        self.files
+            .iter()
+            .enumerate()
+            .flat_map(|(id, spans)| spans.iter().map(move |span| (id as u64, span)))
+            .map(|(id, span)| {
+                let (start, len) = (span.start as u64, span.len as u64);
+                // id * (start + (start + 1) + ... + (start + len - 1))
+                id * (len * start + len * len.saturating_sub(1) / 2)
+            })
+            .sum()
+    }
+}
+
+fn solve_part1(diskmap: &[u8]) -> u64 {
+    let mut disk = DiskMap::from_diskmap(diskmap);
+    disk.compact_fragmenting(|_, _| {});
+    disk.checksum()
 }
 
-fn solve_part2_alt(diskmap: &Vec<u8>) -> u64 {
+fn solve_part2(diskmap: &[u8]) -> u64 {
+    let mut disk = DiskMap::from_diskmap(diskmap);
+    disk.compact_whole_files(|_, _| {});
+    disk.checksum()
+}
+
+fn solve_part2_alt(diskmap: &[u8]) -> u64 {
     let mut blocks: Vec<Option<u64>> = diskmap
         .iter()
         .enumerate()
@@ -128,43 +359,42 @@ fn solve_part2_alt(diskmap: &Vec<u8>) -> u64 {
         .flatten()
         .collect();
 
+    if blocks.is_empty() {
+        return 0;
+    }
     let mut i = blocks.len() - 1;
     let mut last_handled_fileid = None;
     loop {
         if let Some(id) = blocks[i] {
             let mut start_idx = i;
-            while blocks[start_idx - 1].is_some_and(|x| x == id) {
+            while start_idx > 0 && blocks[start_idx - 1].is_some_and(|x| x == id) {
                 start_idx -= 1;
-                if start_idx == 0 {
-                    break;
-                }
             }
-            if last_handled_fileid.is_some_and(|fileid| id >= fileid) {
-                i = start_idx - 1;
-                continue;
-            }
-            let file_size = i - start_idx + 1;
-            for j in 0..i {
-                if blocks[j].is_none() {
-                    let mut end_idx = j;
-                    while blocks[end_idx + 1].is_none() {
-                        end_idx += 1;
-                        if end_idx == blocks.len() - 1 {
-                            break;
+            // files already moved, or left where they were, are passed over
+            if last_handled_fileid.is_none_or(|fileid| id < fileid) {
+                let file_size = i - start_idx + 1;
+                for j in 0..i {
+                    if blocks[j].is_none() {
+                        let mut end_idx = j;
+                        while blocks[end_idx + 1].is_none() {
+                            end_idx += 1;
+                            if end_idx == blocks.len() - 1 {
+                                break;
+                            }
                         }
-                    }
-                    let free_space = end_idx - j + 1;
-                    if free_space >= file_size {
-                        let (p1, p2) = (i, j);
-                        for k in 0..file_size {
-                            blocks.swap(p2 + k, p1 - k);
+                        let free_space = end_idx - j + 1;
+                        if free_space >= file_size {
+                            let (p1, p2) = (i, j);
+                            for k in 0..file_size {
+                                blocks.swap(p2 + k, p1 - k);
+                            }
+                            break;
                         }
-                        break;
                     }
                 }
+                last_handled_fileid = Some(id);
             }
             i = start_idx;
-            last_handled_fileid = Some(id);
         }
         if i == 0 {
             break;
@@ -178,9 +408,326 @@ fn solve_part2_alt(diskmap: &Vec<u8>) -> u64 {
         .sum()
 }
 
-fn blocks_as_string(blocks: &Vec<Option<u64>>) -> String {
-    blocks
-        .iter()
-        .map(|x| x.map(|z| z.to_string()).unwrap_or(".".to_string()))
-        .collect::<String>()
+fn layout(blocks: &[Option<u64>], style: LayoutStyle) -> String {
+    match style {
+        LayoutStyle::Base36 => {
+            let width = id_width(blocks);
+            blocks
+                .iter()
+                .map(|block| block_label(*block, width))
+                .collect()
+        }
+        LayoutStyle::Delimited(delimiter) => blocks
+            .iter()
+            .map(|block| block.map(|id| id.to_string()).unwrap_or(".".to_string()))
+            .collect::<Vec<_>>()
+            .join(&delimiter.to_string()),
+    }
+}
+
+// number of base-36 digits needed for the largest file id on the disk
+fn id_width(blocks: &[Option<u64>]) -> usize {
+    let max_id = blocks.iter().flatten().max().copied().unwrap_or(0);
+    let mut width = 1;
+    while 36_u64.pow(width as u32) <= max_id {
+        width += 1;
+    }
+    width
+}
+
+fn block_label(block: Option<u64>, width: usize) -> String {
+    let Some(mut id) = block else {
+        return ".".repeat(width);
+    };
+    let mut label = vec!['0'; width];
+    for c in label.iter_mut().rev() {
+        *c = char::from_digit((id % 36) as u32, 36).unwrap();
+        id /= 36;
+    }
+    String::from_iter(label)
+}
+
+fn inspect(disk: &DiskMap, style: LayoutStyle) {
+    match disk.to_diskmap() {
+        Some(diskmap) => println!(
+            "diskmap: {}",
+            diskmap.iter().map(|d| d.to_string()).collect::<String>()
+        ),
+        None => println!("diskmap: (layout can't be written as a dense disk map)"),
+    }
+    println!("layout: {}", layout(&disk.blocks(), style));
+
+    println!("files:");
+    for id in 0..disk.files.len() as u64 {
+        let spans = disk
+            .file_spans(id)
+            .iter()
+            .map(|span| format!("{}..{}", span.start, span.start + span.len))
+            .collect::<Vec<_>>();
+        println!("  {id}: {}", spans.join(" "));
+    }
+
+    let stats = disk.fragmentation();
+    println!(
+        "fragmentation: {} of {} files fragmented into {} fragments; {} free blocks in {} spans, largest {}",
+        stats.fragmented_files,
+        stats.files,
+        stats.fragments,
+        stats.free_blocks,
+        stats.free_spans,
+        stats.largest_free_span
+    );
+}
+
+fn visualize_compaction(
+    diskmap: &[u8],
+    whole_files: bool,
+    options: VisualizeOptions,
+) -> io::Result<()> {
+    let mut disk = DiskMap::from_diskmap(diskmap);
+    let mut visualizer = Visualizer::new(options)?;
+    if !visualizer.show(&disk_canvas(&disk.blocks()))? {
+        return Ok(());
+    }
+
+    let mut running = true;
+    let mut result = Ok(());
+    let on_move = |disk: &DiskMap, _: Move| {
+        if running {
+            match visualizer.show(&disk_canvas(&disk.blocks())) {
+                Ok(keep_going) => running = keep_going,
+                Err(e) => {
+                    result = Err(e);
+                    running = false;
+                }
+            }
+        }
+    };
+    if whole_files {
+        disk.compact_whole_files(on_move);
+    } else {
+        disk.compact_fragmenting(on_move);
+    }
+    result?;
+    if running {
+        visualizer.finish()?;
+    }
+    Ok(())
+}
+
+fn disk_canvas(blocks: &[Option<u64>]) -> Canvas {
+    let colors = [
+        Color::Red,
+        Color::Green,
+        Color::Yellow,
+        Color::Blue,
+        Color::Magenta,
+        Color::Cyan,
+    ];
+    let width = id_width(blocks);
+    let rows = blocks.len().div_ceil(VISUALIZE_WIDTH);
+    let mut canvas = Canvas::new(VISUALIZE_WIDTH * width, rows);
+    for (i, block) in blocks.iter().enumerate() {
+        let color = match block {
+            Some(id) => colors[*id as usize % colors.len()],
+            None => Color::DarkGrey,
+        };
+        let (x, y) = ((i % VISUALIZE_WIDTH) * width, i / VISUALIZE_WIDTH);
+        for (dx, c) in block_label(*block, width).chars().enumerate() {
+            canvas.set(x + dx, y, c, Some(color));
+        }
+    }
+    canvas
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    // disk maps like the puzzle's, along with ones with empty files
+    fn diskmap() -> impl Strategy<Value = Vec<u8>> {
+        prop::collection::vec((0_u8..=9, 0_u8..=9), 1..40).prop_map(|pairs| {
+            let mut diskmap: Vec<u8> = pairs
+                .into_iter()
+                .flat_map(|(file, free)| [file, free])
+                .collect();
+            diskmap.pop();
+            diskmap
+        })
+    }
+
+    fn parse_digits(text: &str) -> Vec<u8> {
+        text.bytes().map(|b| b - b'0').collect()
+    }
+
+    // moves the last file block into the first free block, one block at a time
+    fn naive_part1(diskmap: &[u8]) -> u64 {
+        let mut blocks = DiskMap::from_diskmap(diskmap).blocks();
+        let (mut first_free, mut last_file) = (0, blocks.len());
+        loop {
+            while first_free < blocks.len() && blocks[first_free].is_some() {
+                first_free += 1;
+            }
+            while last_file > 0 && blocks[last_file - 1].is_none() {
+                last_file -= 1;
+            }
+            if first_free >= last_file {
+                break;
+            }
+            blocks.swap(first_free, last_file - 1);
+        }
+        blocks
+            .iter()
+            .enumerate()
+            .filter_map(|(i, block)| block.map(|id| id * i as u64))
+            .sum()
+    }
+
+    #[test]
+    fn example() {
+        let diskmap = parse_digits("2333133121414131402");
+        assert_eq!(solve_part1(&diskmap), 1928);
+        assert_eq!(naive_part1(&diskmap), 1928);
+        assert_eq!(solve_part2(&diskmap), 2858);
+        assert_eq!(solve_part2_alt(&diskmap), 2858);
+    }
+
+    #[test]
+    fn empty_files_join_free_spans() {
+        // the gaps either side of file 1 make room for all of file 6
+        let diskmap = parse_digits("03062027868387");
+        let mut disk = DiskMap::from_diskmap(&diskmap);
+        disk.compact_whole_files(|_, _| {});
+        assert_eq!(
+            layout(&disk.blocks(), LayoutStyle::Base36),
+            "66666666.2233.......44444444......55555555.................."
+        );
+        assert_eq!(solve_part2(&diskmap), 2527);
+        assert_eq!(solve_part2_alt(&diskmap), 2527);
+    }
+
+    fn moves(diskmap: &str, whole_files: bool) -> Vec<(u64, usize, usize, usize)> {
+        let mut disk = DiskMap::from_diskmap(&parse_digits(diskmap));
+        let mut moves = Vec::new();
+        let on_move = |_: &DiskMap, m: Move| moves.push((m.file, m.from, m.to, m.len));
+        if whole_files {
+            disk.compact_whole_files(on_move);
+        } else {
+            disk.compact_fragmenting(on_move);
+        }
+        moves
+    }
+
+    #[test]
+    fn fragmenting_moves() {
+        // 0..111....22222 becomes 022111222......
+        assert_eq!(moves("12345", false), [(2, 13, 1, 2), (2, 10, 6, 3)]);
+        assert_eq!(solve_part1(&parse_digits("12345")), 60);
+        assert!(moves("12345", true).is_empty());
+    }
+
+    #[test]
+    fn whole_file_moves() {
+        // files 9, 7, 4 and 2 move, in that order, leaving
+        // 00992111777.44.333....5555.6666.....8888..
+        assert_eq!(
+            moves("2333133121414131402", true),
+            [(9, 40, 2, 2), (7, 32, 8, 3), (4, 19, 12, 2), (2, 11, 4, 1)]
+        );
+    }
+
+    #[test]
+    fn diskmap_round_trip() {
+        for diskmap in [
+            "2333133121414131402",
+            "12345",
+            "10101",
+            "1313",
+            "10302",
+            "15051",
+        ] {
+            let diskmap = parse_digits(diskmap);
+            assert_eq!(
+                DiskMap::from_diskmap(&diskmap).to_diskmap(),
+                Some(diskmap.clone())
+            );
+        }
+
+        // file 1 moves back whole, leaving the free blocks at the end
+        let mut disk = DiskMap::from_diskmap(&parse_digits("1313"));
+        disk.compact_whole_files(|_, _| {});
+        let compacted = disk.to_diskmap().unwrap();
+        assert_eq!(compacted, parse_digits("1016"));
+        assert_eq!(DiskMap::from_diskmap(&compacted).blocks(), disk.blocks());
+
+        // empty file 1 ends up between files 0 and 2 once file 2 moves in front of where it was
+        let mut disk = DiskMap::from_diskmap(&parse_digits("13002"));
+        disk.compact_whole_files(|_, _| {});
+        let compacted = disk.to_diskmap().unwrap();
+        assert_eq!(compacted, parse_digits("100023"));
+        assert_eq!(DiskMap::from_diskmap(&compacted).blocks(), disk.blocks());
+
+        // files split up by part 1 can't be written densely
+        let mut disk = DiskMap::from_diskmap(&parse_digits("12345"));
+        disk.compact_fragmenting(|_, _| {});
+        assert_eq!(disk.to_diskmap(), None);
+    }
+
+    #[test]
+    fn layouts() {
+        let blocks = [Some(0), None, Some(10), Some(36)];
+        assert_eq!(layout(&blocks, LayoutStyle::Base36), "00..0a10");
+        assert_eq!(layout(&blocks, LayoutStyle::Delimited(',')), "0,.,10,36");
+
+        let disk = DiskMap::from_diskmap(&parse_digits("101010101010101010101"));
+        assert_eq!(layout(&disk.blocks(), LayoutStyle::Base36), "0123456789a");
+    }
+
+    #[test]
+    fn fragmentation_after_part1() {
+        let mut disk = DiskMap::from_diskmap(&parse_digits("12345"));
+        disk.compact_fragmenting(|_, _| {});
+        assert_eq!(
+            layout(&disk.blocks(), LayoutStyle::Base36),
+            "022111222......"
+        );
+        assert_eq!(
+            disk.file_spans(2),
+            [Span { start: 1, len: 2 }, Span { start: 6, len: 3 }]
+        );
+        assert_eq!(
+            disk.fragmentation(),
+            FragmentationStats {
+                files: 3,
+                fragmented_files: 1,
+                fragments: 4,
+                free_blocks: 6,
+                free_spans: 1,
+                largest_free_span: 6,
+            }
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn whole_file_compaction_agrees(diskmap in diskmap()) {
+            prop_assert_eq!(solve_part2_alt(&diskmap), solve_part2(&diskmap));
+        }
+
+        #[test]
+        fn diskmaps_round_trip(diskmap in diskmap()) {
+            let mut disk = DiskMap::from_diskmap(&diskmap);
+            prop_assert_eq!(disk.to_diskmap(), Some(diskmap));
+            disk.compact_whole_files(|_, _| {});
+            if let Some(compacted) = disk.to_diskmap() {
+                prop_assert_eq!(DiskMap::from_diskmap(&compacted).blocks(), disk.blocks());
+            }
+        }
+
+        #[test]
+        fn fragmenting_compaction_agrees(diskmap in diskmap()) {
+            prop_assert_eq!(solve_part1(&diskmap), naive_part1(&diskmap));
+        }
+    }
 }
@@ -0,0 +1,175 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt, fs,
+};
+
+use crate::parse::{int, pair, parse_line, spaces1, terminated};
+
+/// The two location lists, in the order they were read.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Lists {
+    pub left: Vec<u64>,
+    pub right: Vec<u64>,
+}
+
+/// A line that couldn't be read as two location ids, which is left out of the lists.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LineError {
+    /// The line number, counting from 1.
+    pub line: usize,
+    pub content: String,
+    pub reason: String,
+}
+
+impl fmt::Display for LineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "line {}: {} ({:?})",
+            self.line, self.reason, self.content
+        )
+    }
+}
+
+/// Reads a location id from each column of every line.
+pub fn parse_lists(input: &str) -> (Lists, Vec<LineError>) {
+    let mut lists = Lists::default();
+    let mut errors = Vec::new();
+    for (i, line) in input.lines().enumerate() {
+        let error = |reason: String| LineError {
+            line: i + 1,
+            content: line.to_string(),
+            reason,
+        };
+        if line.trim().is_empty() {
+            errors.push(error("blank line".to_string()));
+            continue;
+        }
+        match parse_line(line, i + 1, pair(terminated(int(), spaces1()), int())) {
+            Ok((left, right)) => {
+                lists.left.push(left);
+                lists.right.push(right);
+            }
+            Err(e) => errors.push(error(format!("column {}: {}", e.column, e.message))),
+        }
+    }
+    (lists, errors)
+}
+
+pub fn read_lists(path: &str) -> (Lists, Vec<LineError>) {
+    let input = fs::read_to_string(path).expect("couldn't read input file");
+    parse_lists(&input)
+}
+
+impl Lists {
+    /// The distance between the smallest ids of each list, the second smallest and so on.
+    pub fn differences(&self) -> Vec<u64> {
+        let mut left = self.left.clone();
+        let mut right = self.right.clone();
+        left.sort();
+        right.sort();
+        left.iter()
+            .zip(&right)
+            .map(|(l, r)| l.abs_diff(*r))
+            .collect()
+    }
+
+    pub fn total_distance(&self) -> u64 {
+        self.differences().iter().sum()
+    }
+
+    /// Each id in the left list times the number of times it appears in the right list,
+    /// added up.
+    pub fn similarity_score(&self) -> u64 {
+        let counts = counts(&self.right);
+        self.left
+            .iter()
+            .map(|id| id * counts.get(id).copied().unwrap_or(0) as u64)
+            .sum()
+    }
+
+    /// How many pairs are each distance apart.
+    pub fn difference_histogram(&self) -> BTreeMap<u64, usize> {
+        let mut histogram = BTreeMap::new();
+        for difference in self.differences() {
+            *histogram.entry(difference).or_default() += 1;
+        }
+        histogram
+    }
+}
+
+fn counts(values: &[u64]) -> HashMap<u64, usize> {
+    let mut counts = HashMap::new();
+    for value in values {
+        *counts.entry(*value).or_default() += 1;
+    }
+    counts
+}
+
+/// The middle value, or the mean of the two middle values when there's an even number.
+pub fn median(values: &[u64]) -> Option<f64> {
+    let mut sorted = values.to_vec();
+    sorted.sort();
+    let mid = sorted.len() / 2;
+    match sorted.len() {
+        0 => None,
+        n if n % 2 == 1 => Some(sorted[mid] as f64),
+        _ => Some((sorted[mid - 1] as f64 + sorted[mid] as f64) / 2.0),
+    }
+}
+
+/// The most common value, or the smallest of them when there's a tie.
+pub fn mode(values: &[u64]) -> Option<u64> {
+    counts(values)
+        .into_iter()
+        .max_by(|(a, a_count), (b, b_count)| a_count.cmp(b_count).then(b.cmp(a)))
+        .map(|(value, _)| value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "3   4\n4   3\n2   5\n1   3\n3   9\n3   3\n";
+
+    #[test]
+    fn example() {
+        let (lists, errors) = parse_lists(EXAMPLE);
+        assert_eq!(errors, []);
+        assert_eq!(lists.total_distance(), 11);
+        assert_eq!(lists.similarity_score(), 31);
+        assert_eq!(lists.differences(), [2, 1, 0, 1, 2, 5]);
+        assert_eq!(
+            lists.difference_histogram(),
+            BTreeMap::from([(0, 1), (1, 2), (2, 2), (5, 1)])
+        );
+    }
+
+    #[test]
+    fn medians_and_modes() {
+        assert_eq!(median(&[3, 1, 2]), Some(2.0));
+        assert_eq!(median(&[2, 1, 0, 1, 2, 5]), Some(1.5));
+        assert_eq!(median(&[]), None);
+        // 1 and 2 both appear twice
+        assert_eq!(mode(&[2, 1, 0, 1, 2, 5]), Some(1));
+        assert_eq!(mode(&[7, 7, 3]), Some(7));
+        assert_eq!(mode(&[]), None);
+    }
+
+    #[test]
+    fn line_errors() {
+        let (lists, errors) = parse_lists("3   4\n\n4 x\n5 6\n   \n7");
+        assert_eq!(lists.left, [3, 5]);
+        assert_eq!(lists.right, [4, 6]);
+        let errors = errors.iter().map(|e| e.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            errors,
+            [
+                r#"line 2: blank line ("")"#,
+                r#"line 3: column 3: expected a number, found 'x' ("4 x")"#,
+                r#"line 5: blank line ("   ")"#,
+                r#"line 6: column 2: expected a space, found the end of the line ("7")"#,
+            ]
+        );
+    }
+}
@@ -0,0 +1,237 @@
+use std::{fs, ops::RangeInclusive};
+
+use crate::parse::{self, parse_lines, spaced};
+
+pub type Level = u64;
+
+/// Decides whether reports are safe: their levels all increase or all decrease, by a step
+/// in `steps` each time, once at most `max_removals` of them are taken out.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Analyser {
+    pub steps: RangeInclusive<Level>,
+    pub max_removals: usize,
+}
+
+impl Default for Analyser {
+    fn default() -> Self {
+        Self {
+            steps: 1..=3,
+            max_removals: 0,
+        }
+    }
+}
+
+impl Analyser {
+    /// Reads `--removals <k>` and `--steps <a>..<b>|<a>..=<b>` from the arguments, starting
+    /// from `max_removals` removals and steps of 1 to 3.
+    pub fn from_args(args: &[String], max_removals: usize) -> Self {
+        let mut analyser = Self {
+            max_removals,
+            ..Self::default()
+        };
+        let value_of = |flag: &str| {
+            args.iter().position(|a| a == flag).map(|i| {
+                args.get(i + 1)
+                    .unwrap_or_else(|| panic!("{flag} needs a value"))
+                    .as_str()
+            })
+        };
+        if let Some(removals) = value_of("--removals") {
+            analyser.max_removals = removals.parse().expect("invalid number of removals");
+        }
+        if let Some(steps) = value_of("--steps") {
+            analyser.steps =
+                parse::range(steps).unwrap_or_else(|| panic!("invalid step range '{steps}'"));
+        }
+        analyser
+    }
+
+    pub fn is_safe(&self, report: &[Level]) -> bool {
+        self.removals(report).is_some()
+    }
+
+    /// The positions of the fewest levels to take out of the report to make it safe, or
+    /// `None` if that takes more than `max_removals`. A report that's already safe needs
+    /// none taken out.
+    pub fn removals(&self, report: &[Level]) -> Option<Vec<usize>> {
+        [true, false]
+            .into_iter()
+            .filter_map(|increasing| self.removals_in_direction(report, increasing))
+            .min_by_key(|removals| removals.len())
+    }
+
+    // the fewest removals leaving levels that change in the given direction. For each level,
+    // this finds the fewest removals up to it that keep it, which only depends on the last
+    // `max_removals + 1` levels before it, so it takes O(n·k) time for k removals.
+    fn removals_in_direction(&self, report: &[Level], increasing: bool) -> Option<Vec<usize>> {
+        let n = report.len();
+        if n <= 1 {
+            return Some(Vec::new());
+        }
+        let step_ok = |from: Level, to: Level| {
+            let step = if increasing {
+                to.checked_sub(from)
+            } else {
+                from.checked_sub(to)
+            };
+            step.is_some_and(|step| self.steps.contains(&step))
+        };
+
+        // fewest removals among the first i levels if level i is kept, and the level kept
+        // before it
+        let mut fewest: Vec<usize> = Vec::with_capacity(n);
+        let mut previous: Vec<Option<usize>> = Vec::with_capacity(n);
+        for i in 0..n {
+            let mut best = (i, None);
+            for j in i.saturating_sub(self.max_removals + 1)..i {
+                let removed = fewest[j] + (i - j - 1);
+                if removed < best.0 && step_ok(report[j], report[i]) {
+                    best = (removed, Some(j));
+                }
+            }
+            fewest.push(best.0);
+            previous.push(best.1);
+        }
+
+        let (removed, last) = (0..n).map(|i| (fewest[i] + (n - 1 - i), i)).min()?;
+        if removed > self.max_removals {
+            return None;
+        }
+        let mut kept = vec![false; n];
+        let mut level = Some(last);
+        while let Some(i) = level {
+            kept[i] = true;
+            level = previous[i];
+        }
+        Some((0..n).filter(|i| !kept[*i]).collect())
+    }
+}
+
+/// Reads one report per line, each a list of levels separated by whitespace.
+pub fn parse_input(path: &str) -> Vec<Vec<Level>> {
+    let input = fs::read_to_string(path).expect("couldn't read input file");
+    parse_lines(&input, 1, spaced()).unwrap_or_else(|e| panic!("invalid report at {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use itertools::Itertools;
+    use proptest::prelude::*;
+
+    fn steadily_changes(analyser: &Analyser, levels: &[Level]) -> bool {
+        let step = |a: Level, b: Level, increasing: bool| {
+            let step = if increasing {
+                b.checked_sub(a)
+            } else {
+                a.checked_sub(b)
+            };
+            step.is_some_and(|step| analyser.steps.contains(&step))
+        };
+        [true, false]
+            .into_iter()
+            .any(|increasing| levels.windows(2).all(|w| step(w[0], w[1], increasing)))
+    }
+
+    // the fewest removals making the report safe, trying every set of levels to take out
+    fn naive_removals(analyser: &Analyser, report: &[Level]) -> Option<usize> {
+        (0..=analyser.max_removals.min(report.len())).find(|k| {
+            (0..report.len()).combinations(*k).any(|removed| {
+                let kept: Vec<Level> = (0..report.len())
+                    .filter(|i| !removed.contains(i))
+                    .map(|i| report[i])
+                    .collect();
+                steadily_changes(analyser, &kept)
+            })
+        })
+    }
+
+    #[test]
+    fn example() {
+        let reports = [
+            vec![7, 6, 4, 2, 1],
+            vec![1, 2, 7, 8, 9],
+            vec![9, 7, 6, 2, 1],
+            vec![1, 3, 2, 4, 5],
+            vec![8, 6, 4, 4, 1],
+            vec![1, 3, 6, 7, 9],
+        ];
+        let safe = |analyser: Analyser| reports.iter().filter(|r| analyser.is_safe(r)).count();
+        assert_eq!(safe(Analyser::default()), 2);
+        assert_eq!(
+            safe(Analyser {
+                max_removals: 1,
+                ..Analyser::default()
+            }),
+            4
+        );
+    }
+
+    #[test]
+    fn removal_limits() {
+        let analyser = |max_removals| Analyser {
+            max_removals,
+            ..Analyser::default()
+        };
+        // with none allowed, only reports that are already safe are
+        assert_eq!(analyser(0).removals(&[1, 2, 4]), Some(vec![]));
+        assert_eq!(analyser(0).removals(&[1, 2, 2]), None);
+        assert_eq!(analyser(0).removals(&[]), Some(vec![]));
+        // with as many as there are levels, any report is safe once all but one are gone
+        assert_eq!(analyser(3).removals(&[5, 5, 5]).map(|r| r.len()), Some(2));
+        assert_eq!(
+            analyser(9).removals(&[1, 9, 1, 9]).map(|r| r.len()),
+            Some(3)
+        );
+        assert_eq!(analyser(2).removals(&[1, 9, 1, 9]), None);
+    }
+
+    #[test]
+    fn steps_from_args() {
+        let args = |steps: &str| ["--steps", steps, "--removals", "2"].map(String::from);
+        let analyser = Analyser::from_args(&args("2..5"), 0);
+        assert_eq!((analyser.steps, analyser.max_removals), (2..=4, 2));
+        assert_eq!(Analyser::from_args(&args("2..=5"), 0).steps, 2..=5);
+        assert_eq!(Analyser::from_args(&args("3"), 0).steps, 3..=3);
+        assert_eq!(
+            Analyser::from_args(&[], 1),
+            Analyser {
+                max_removals: 1,
+                ..Analyser::default()
+            }
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid step range '1..0'")]
+    fn empty_step_range() {
+        Analyser::from_args(&["--steps", "1..0"].map(String::from), 0);
+    }
+
+    proptest! {
+        #[test]
+        fn removals_agree_with_naive(
+            report in prop::collection::vec(1_u64..12, 0..9),
+            max_removals in 0_usize..4,
+            first_step in 0_u64..3,
+            extra_steps in 0_u64..3,
+        ) {
+            let analyser = Analyser {
+                steps: first_step..=first_step + extra_steps,
+                max_removals,
+            };
+            let removals = analyser.removals(&report);
+            prop_assert_eq!(
+                removals.as_ref().map(|removals| removals.len()),
+                naive_removals(&analyser, &report)
+            );
+            if let Some(removals) = removals {
+                let kept: Vec<Level> = (0..report.len())
+                    .filter(|i| !removals.contains(i))
+                    .map(|i| report[i])
+                    .collect();
+                prop_assert!(steadily_changes(&analyser, &kept));
+            }
+        }
+    }
+}
@@ -0,0 +1,433 @@
+use std::{
+    io::{self, Read},
+    ops::RangeInclusive,
+};
+
+// how much of the input is read at a time when scanning a reader
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// An instruction found in corrupted memory.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Instr {
+    Mul(u64, u64),
+    Do,
+    Dont,
+    /// Any other instruction the language was extended with.
+    Call(&'static str, Vec<u64>),
+}
+
+/// An instruction and the byte offset in the input it starts at.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Token {
+    pub instr: Instr,
+    pub offset: usize,
+}
+
+/// How an instruction is written: its name followed by `arity` comma-separated numbers in
+/// brackets, e.g. `mul(2,4)`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Syntax {
+    pub name: &'static str,
+    pub arity: usize,
+}
+
+/// The instructions hidden in corrupted memory and how they're written. Anything else is
+/// skipped over.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Language {
+    pub instructions: Vec<Syntax>,
+    /// How many digits each number can have.
+    pub digits: RangeInclusive<usize>,
+}
+
+impl Default for Language {
+    fn default() -> Self {
+        Self {
+            instructions: vec![
+                Syntax {
+                    name: "mul",
+                    arity: 2,
+                },
+                Syntax {
+                    name: "do",
+                    arity: 0,
+                },
+                Syntax {
+                    name: "don't",
+                    arity: 0,
+                },
+            ],
+            digits: 1..=usize::MAX,
+        }
+    }
+}
+
+impl Language {
+    /// Reads `--max-digits <n>` from the arguments, only accepting numbers of up to n digits.
+    pub fn from_args(args: &[String]) -> Self {
+        match args.iter().position(|a| a == "--max-digits") {
+            Some(i) => {
+                let max = args
+                    .get(i + 1)
+                    .and_then(|n| n.parse().ok())
+                    .expect("--max-digits needs a number");
+                Self::default().with_digits(1..=max)
+            }
+            None => Self::default(),
+        }
+    }
+
+    pub fn with_instruction(mut self, name: &'static str, arity: usize) -> Self {
+        self.instructions.push(Syntax { name, arity });
+        self
+    }
+
+    pub fn with_digits(mut self, digits: RangeInclusive<usize>) -> Self {
+        self.digits = digits;
+        self
+    }
+
+    /// Every instruction in the input, in order.
+    pub fn tokenize(&self, input: &str) -> Vec<Token> {
+        let mut tokens = Vec::new();
+        self.scan(input.as_bytes(), 0, true, &mut tokens);
+        tokens
+    }
+
+    /// Every instruction read from `reader`, in order, reading it a chunk at a time.
+    pub fn tokenize_reader(&self, mut reader: impl Read) -> io::Result<Vec<Token>> {
+        let mut scanner = Scanner::new(self);
+        let mut tokens = Vec::new();
+        let mut buf = vec![0; CHUNK_SIZE];
+        loop {
+            let n = reader.read(&mut buf)?;
+            if n == 0 {
+                break;
+            }
+            tokens.extend(scanner.feed(&buf[..n]));
+        }
+        tokens.extend(scanner.finish());
+        Ok(tokens)
+    }
+
+    // adds the instructions in `input`, which starts `base` bytes into the whole input, to
+    // `tokens`. Unless `last` is set, more input may follow, so scanning stops at the first
+    // instruction that might continue past the end. Returns how many bytes were scanned.
+    fn scan(&self, input: &[u8], base: usize, last: bool, tokens: &mut Vec<Token>) -> usize {
+        let mut offset = 0;
+        while offset < input.len() {
+            match self.instruction_at(&input[offset..], last) {
+                Match::Complete(instr, len) => {
+                    tokens.push(Token {
+                        instr,
+                        offset: base + offset,
+                    });
+                    offset += len;
+                }
+                Match::Incomplete => break,
+                Match::None => offset += 1,
+            }
+        }
+        offset
+    }
+
+    // the instruction at the start of `input`. When an earlier instruction in the list
+    // might still match given more input, that has to be known first, unless `last` says
+    // there's no more input to come.
+    fn instruction_at(&self, input: &[u8], last: bool) -> Match {
+        let mut incomplete = false;
+        for syntax in &self.instructions {
+            match self.parse_call(syntax, input) {
+                Match::Complete(instr, len) if !incomplete => {
+                    return Match::Complete(instr, len);
+                }
+                Match::None => {}
+                Match::Incomplete if last => {}
+                _ => incomplete = true,
+            }
+        }
+        if incomplete {
+            Match::Incomplete
+        } else {
+            Match::None
+        }
+    }
+
+    fn parse_call(&self, syntax: &Syntax, input: &[u8]) -> Match {
+        let name = syntax.name.as_bytes();
+        if input.len() < name.len() {
+            return if name.starts_with(input) {
+                Match::Incomplete
+            } else {
+                Match::None
+            };
+        }
+        if !input.starts_with(name) {
+            return Match::None;
+        }
+        let mut pos = name.len();
+        match input.get(pos) {
+            None => return Match::Incomplete,
+            Some(b'(') => pos += 1,
+            Some(_) => return Match::None,
+        }
+
+        let mut args = Vec::with_capacity(syntax.arity);
+        for i in 0..syntax.arity {
+            if i > 0 {
+                match input.get(pos) {
+                    None => return Match::Incomplete,
+                    Some(b',') => pos += 1,
+                    Some(_) => return Match::None,
+                }
+            }
+            let digits = input[pos..]
+                .iter()
+                .take_while(|b| b.is_ascii_digit())
+                .count();
+            if pos + digits == input.len() && digits < *self.digits.end() {
+                return Match::Incomplete;
+            }
+            if !self.digits.contains(&digits) {
+                return Match::None;
+            }
+            let Some(value) = input[pos..pos + digits].iter().try_fold(0_u64, |n, d| {
+                n.checked_mul(10)?.checked_add((d - b'0') as u64)
+            }) else {
+                return Match::None;
+            };
+            args.push(value);
+            pos += digits;
+        }
+
+        match input.get(pos) {
+            None => Match::Incomplete,
+            Some(b')') => Match::Complete(to_instr(syntax, args), pos + 1),
+            Some(_) => Match::None,
+        }
+    }
+}
+
+// what's at the start of some input
+enum Match {
+    // an instruction, and how many bytes it takes up
+    Complete(Instr, usize),
+    // the input ends partway through what could still be an instruction
+    Incomplete,
+    None,
+}
+
+/// Finds instructions in input that arrives a chunk at a time. Instructions split between
+/// chunks are found all the same, with the same offsets as if the input came all at once.
+pub struct Scanner<'a> {
+    language: &'a Language,
+    // input that might be the start of an instruction continuing into the next chunk
+    pending: Vec<u8>,
+    // offset of the start of `pending` in the whole input
+    offset: usize,
+}
+
+impl<'a> Scanner<'a> {
+    pub fn new(language: &'a Language) -> Self {
+        Self {
+            language,
+            pending: Vec::new(),
+            offset: 0,
+        }
+    }
+
+    /// The instructions that can be found so far, after adding `chunk` to the input.
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<Token> {
+        self.pending.extend_from_slice(chunk);
+        let mut tokens = Vec::new();
+        let scanned = self
+            .language
+            .scan(&self.pending, self.offset, false, &mut tokens);
+        self.pending.drain(..scanned);
+        self.offset += scanned;
+        tokens
+    }
+
+    /// The instructions left at the end of the input.
+    pub fn finish(self) -> Vec<Token> {
+        let mut tokens = Vec::new();
+        self.language
+            .scan(&self.pending, self.offset, true, &mut tokens);
+        tokens
+    }
+}
+
+fn to_instr(syntax: &Syntax, args: Vec<u64>) -> Instr {
+    match (syntax.name, args.as_slice()) {
+        ("mul", [a, b]) => Instr::Mul(*a, *b),
+        ("do", []) => Instr::Do,
+        ("don't", []) => Instr::Dont,
+        _ => Instr::Call(syntax.name, args),
+    }
+}
+
+/// The state of a running program.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Machine {
+    pub enabled: bool,
+    /// `None` once the total is too big for a u64.
+    pub total: Option<u64>,
+}
+
+impl Default for Machine {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            total: Some(0),
+        }
+    }
+}
+
+impl Machine {
+    pub fn add_product(&mut self, a: u64, b: u64) {
+        self.total = self
+            .total
+            .and_then(|total| a.checked_mul(b)?.checked_add(total));
+    }
+}
+
+/// What each instruction does.
+pub trait Semantics {
+    fn execute(&self, instr: &Instr, machine: &mut Machine);
+}
+
+/// Adds up the products of every `mul` and ignores everything else.
+pub struct MulOnly;
+
+impl Semantics for MulOnly {
+    fn execute(&self, instr: &Instr, machine: &mut Machine) {
+        if let Instr::Mul(a, b) = instr {
+            machine.add_product(*a, *b);
+        }
+    }
+}
+
+/// Adds up the products of the `mul`s that come after a `do()` (or before any `don't()`),
+/// skipping those after a `don't()`.
+pub struct Conditional;
+
+impl Semantics for Conditional {
+    fn execute(&self, instr: &Instr, machine: &mut Machine) {
+        match instr {
+            Instr::Do => machine.enabled = true,
+            Instr::Dont => machine.enabled = false,
+            Instr::Mul(a, b) if machine.enabled => machine.add_product(*a, *b),
+            _ => {}
+        }
+    }
+}
+
+/// Runs the instructions from the start, returning the final total, or `None` if it's too
+/// big for a u64.
+pub fn run<'a>(
+    program: impl IntoIterator<Item = &'a Token>,
+    semantics: &dyn Semantics,
+) -> Option<u64> {
+    let mut machine = Machine::default();
+    for token in program {
+        semantics.execute(&token.instr, &mut machine);
+    }
+    machine.total
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    // bits of instructions and other noise, so random inputs are full of near misses
+    const PIECES: &[&str] = &[
+        "mul(", "do()", "don't()", "do", "don", "n't", "(", ")", ",", "1", "23", "456", "7890",
+        "mul(2,3)", "x", "\n", "?", "mu", "l",
+    ];
+
+    fn scan_chunked(language: &Language, input: &[u8], sizes: &[usize]) -> Vec<Token> {
+        let mut scanner = Scanner::new(language);
+        let mut tokens = Vec::new();
+        let mut rest = input;
+        for size in sizes.iter().cycle() {
+            if rest.is_empty() {
+                break;
+            }
+            let (chunk, tail) = rest.split_at((*size).min(rest.len()));
+            tokens.extend(scanner.feed(chunk));
+            rest = tail;
+        }
+        tokens.extend(scanner.finish());
+        tokens
+    }
+
+    #[test]
+    fn overflowing_totals() {
+        let language = Language::default();
+        let big = language.tokenize("mul(9999999999,9999999999)");
+        assert_eq!(run(&big, &MulOnly), None);
+        let max = language.tokenize(&format!("mul({},1)mul(1,1)", u64::MAX));
+        assert_eq!(run(&max[..1], &MulOnly), Some(u64::MAX));
+        assert_eq!(run(&max, &MulOnly), None);
+        // products after a `don't()` can't overflow the total
+        let disabled = language.tokenize("mul(2,3)don't()mul(9999999999,9999999999)");
+        assert_eq!(run(&disabled, &Conditional), Some(6));
+    }
+
+    #[test]
+    fn language_from_args() {
+        let args = ["--tokens", "--max-digits", "2"].map(String::from);
+        let language = Language::from_args(&args);
+        assert_eq!(language, Language::default().with_digits(1..=2));
+        assert_eq!(language.tokenize("mul(12,345)mul(1,23)").len(), 1);
+        assert_eq!(Language::from_args(&[]), Language::default());
+    }
+
+    #[test]
+    fn example() {
+        let input = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+        let program = Language::default().tokenize(input);
+        assert_eq!(run(&program, &MulOnly), Some(161));
+        assert_eq!(run(&program, &Conditional), Some(48));
+        assert_eq!(
+            program[..2],
+            [
+                Token {
+                    instr: Instr::Mul(2, 4),
+                    offset: 1
+                },
+                Token {
+                    instr: Instr::Dont,
+                    offset: 20
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn instruction_split_across_lines_is_ignored() {
+        // only `mul(4,5)` is whole
+        let input = "mul(1\n2,3)mul(4,5)\nmul(6,\n7)";
+        let tokens = Language::default()
+            .tokenize_reader(input.as_bytes())
+            .unwrap();
+        assert_eq!(run(&tokens, &MulOnly), Some(20));
+    }
+
+    proptest! {
+        #[test]
+        fn chunked_scan_matches_whole_scan(
+            pieces in prop::collection::vec(prop::sample::select(PIECES), 0..60),
+            sizes in prop::collection::vec(1_usize..16, 1..8),
+            max_digits in prop_oneof![Just(3_usize), Just(usize::MAX)],
+        ) {
+            let input = pieces.concat();
+            let language = Language::default()
+                .with_instruction("add", 2)
+                .with_digits(1..=max_digits);
+            let whole = language.tokenize(&input);
+            prop_assert_eq!(scan_chunked(&language, input.as_bytes(), &sizes), whole);
+        }
+    }
+}
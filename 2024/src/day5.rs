@@ -0,0 +1,354 @@
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    fmt, fs,
+};
+
+use crate::parse::{delimited, int, pair, sections, tag, terminated};
+
+pub type Page = u64;
+
+/// The page ordering rules, as a directed graph with an edge from `a` to `b` for every `a|b`
+/// rule, meaning page `a` has to be printed before page `b`.
+#[derive(Clone, Debug, Default)]
+pub struct RuleSet {
+    // the pages each page has to come before, with the number of the rule saying so
+    successors: HashMap<Page, HashMap<Page, usize>>,
+    count: usize,
+}
+
+/// Two pages of an update in the wrong order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Violation {
+    /// The `a|b` rule that was broken, as `(a, b)`.
+    pub rule: (Page, Page),
+    /// The rule's number, counting from 1 in the order the rules were added. For rules read
+    /// by `parse_input` this is also their line in the input.
+    pub rule_number: usize,
+    /// Where `a` and `b` are in the update, counting from 0. `b` comes first.
+    pub positions: (usize, usize),
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (a, b) = self.rule;
+        let (i, j) = self.positions;
+        write!(
+            f,
+            "{b} at position {} comes before {a} at position {}, breaking rule {a}|{b} (line {})",
+            j + 1,
+            i + 1,
+            self.rule_number
+        )
+    }
+}
+
+/// Why an update has no single correct order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OrderError {
+    /// The rules between the update's pages go round in a circle, each page having to come
+    /// before the next and the last before the first.
+    Cycle(Vec<Page>),
+    /// No rule decides which of these two pages comes first.
+    Ambiguous(Page, Page),
+    /// The page is in the update more than once.
+    Duplicate(Page),
+}
+
+impl fmt::Display for OrderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OrderError::Cycle(pages) => {
+                let pages = pages.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+                write!(f, "cycle {} -> {}", pages.join(" -> "), pages[0])
+            }
+            OrderError::Ambiguous(a, b) => write!(f, "no rule orders {a} and {b}"),
+            OrderError::Duplicate(page) => write!(f, "page {page} appears more than once"),
+        }
+    }
+}
+
+impl RuleSet {
+    pub fn add_rule(&mut self, before: Page, after: Page) {
+        self.count += 1;
+        self.successors
+            .entry(before)
+            .or_default()
+            .entry(after)
+            .or_insert(self.count);
+    }
+
+    /// Whether a rule says `a` has to come before `b`.
+    pub fn must_precede(&self, a: Page, b: Page) -> bool {
+        self.rule_number(a, b).is_some()
+    }
+
+    fn rule_number(&self, a: Page, b: Page) -> Option<usize> {
+        self.successors.get(&a).and_then(|s| s.get(&b)).copied()
+    }
+
+    /// Every pair of pages in the update that's in an order the rules don't allow, in the
+    /// order the later page of each pair appears.
+    pub fn violations(&self, update: &[Page]) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        for (i, page) in update.iter().enumerate() {
+            for (j, earlier) in update[..i].iter().enumerate() {
+                if let Some(rule_number) = self.rule_number(*page, *earlier) {
+                    violations.push(Violation {
+                        rule: (*page, *earlier),
+                        rule_number,
+                        positions: (i, j),
+                    });
+                }
+            }
+        }
+        violations
+    }
+
+    /// Whether every pair of pages in the update is in an order the rules allow.
+    pub fn is_valid(&self, update: &[Page]) -> bool {
+        self.violations(update).is_empty()
+    }
+
+    /// Puts the update's pages in the one order the rules between them allow. Rules about
+    /// pages that aren't in the update are ignored, and an update with a page in it twice
+    /// has no order.
+    pub fn repair(&self, update: &[Page]) -> Result<Vec<Page>, OrderError> {
+        let mut pages = HashSet::new();
+        if let Some(page) = update.iter().find(|page| !pages.insert(**page)) {
+            return Err(OrderError::Duplicate(*page));
+        }
+        let successors = |page: Page| {
+            self.successors
+                .get(&page)
+                .into_iter()
+                .flat_map(|s| s.keys())
+                .copied()
+                .filter(|p| pages.contains(p))
+        };
+
+        let mut predecessors: HashMap<Page, usize> = pages.iter().map(|p| (*p, 0)).collect();
+        for page in &pages {
+            for successor in successors(*page) {
+                *predecessors.get_mut(&successor).unwrap() += 1;
+            }
+        }
+
+        // Kahn's algorithm, taking the smallest ready page each time so the result doesn't
+        // depend on hashing order
+        let mut ready: BTreeSet<Page> = predecessors
+            .iter()
+            .filter(|(_, n)| **n == 0)
+            .map(|(p, _)| *p)
+            .collect();
+        let mut order = Vec::new();
+        while let Some(page) = ready.pop_first() {
+            order.push(page);
+            for successor in successors(page) {
+                let n = predecessors.get_mut(&successor).unwrap();
+                *n -= 1;
+                if *n == 0 {
+                    ready.insert(successor);
+                }
+            }
+        }
+
+        if order.len() < pages.len() {
+            let placed: HashSet<Page> = order.iter().copied().collect();
+            let remaining: HashSet<Page> = pages.difference(&placed).copied().collect();
+            return Err(OrderError::Cycle(self.find_cycle(&remaining)));
+        }
+
+        // the order is the only one possible exactly when a rule links every page to the next
+        if let Some(pair) = order.windows(2).find(|w| !self.must_precede(w[0], w[1])) {
+            return Err(OrderError::Ambiguous(pair[0], pair[1]));
+        }
+        Ok(order)
+    }
+
+    // every page Kahn's algorithm couldn't place still has a predecessor that couldn't be
+    // placed either, so walking back through those must eventually come round in a circle
+    fn find_cycle(&self, remaining: &HashSet<Page>) -> Vec<Page> {
+        let mut start = *remaining.iter().min().unwrap();
+        let mut seen = HashMap::new();
+        let mut path = Vec::new();
+        while !seen.contains_key(&start) {
+            seen.insert(start, path.len());
+            path.push(start);
+            start = *remaining
+                .iter()
+                .filter(|p| self.must_precede(**p, start))
+                .min()
+                .unwrap();
+        }
+        let mut cycle = path.split_off(seen[&start]);
+        // the walk went backwards, against the rules
+        cycle.reverse();
+        cycle
+    }
+}
+
+/// Reads the `a|b` rules and the comma-separated updates following them from a file.
+pub fn parse_input(path: &str) -> (RuleSet, Vec<Vec<Page>>) {
+    parse(&fs::read_to_string(path).expect("couldn't read input file"))
+}
+
+/// Reads the `a|b` rules and the comma-separated updates following them.
+pub fn parse(input: &str) -> (RuleSet, Vec<Vec<Page>>) {
+    let [rules_section, updates_section] = sections(input)[..] else {
+        panic!("expected the rules and the updates separated by a blank line");
+    };
+
+    let mut rules = RuleSet::default();
+    let rule = pair(terminated(int(), tag("|")), int());
+    for (before, after) in rules_section
+        .parse_lines(rule)
+        .unwrap_or_else(|e| panic!("invalid rule at {e}"))
+    {
+        rules.add_rule(before, after);
+    }
+
+    let updates = updates_section
+        .parse_lines(delimited(","))
+        .unwrap_or_else(|e| panic!("invalid update at {e}"));
+
+    (rules, updates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+47|53
+97|13
+97|61
+97|47
+75|29
+61|13
+75|53
+29|13
+97|29
+53|29
+61|53
+97|53
+61|29
+47|13
+75|47
+97|75
+47|61
+75|61
+47|29
+75|13
+53|13
+
+75,47,61,53,29
+97,61,53,29,13
+75,29,13
+75,97,47,61,53
+61,13,29
+97,13,75,29,47
+";
+
+    fn rules(rules: &[(Page, Page)]) -> RuleSet {
+        let mut set = RuleSet::default();
+        for (before, after) in rules {
+            set.add_rule(*before, *after);
+        }
+        set
+    }
+
+    #[test]
+    fn example() {
+        let (rules, updates) = parse(EXAMPLE);
+        let middle = |update: &[Page]| update[update.len() / 2];
+        let (valid, invalid): (Vec<_>, Vec<_>) =
+            updates.iter().partition(|update| rules.is_valid(update));
+        assert_eq!(valid.iter().map(|u| middle(u)).sum::<Page>(), 143);
+        let repaired = invalid
+            .iter()
+            .map(|update| rules.repair(update).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            repaired,
+            [
+                vec![97, 75, 47, 61, 53],
+                vec![61, 29, 13],
+                vec![97, 75, 47, 29, 13]
+            ]
+        );
+        assert_eq!(repaired.iter().map(|u| middle(u)).sum::<Page>(), 123);
+    }
+
+    #[test]
+    fn violations() {
+        let (rules, updates) = parse(EXAMPLE);
+        let violation = |rule, rule_number, positions| Violation {
+            rule,
+            rule_number,
+            positions,
+        };
+        assert_eq!(rules.violations(&updates[0]), []);
+        assert_eq!(
+            rules.violations(&updates[3]),
+            [violation((97, 75), 16, (1, 0))]
+        );
+        assert_eq!(
+            rules.violations(&updates[4]),
+            [violation((29, 13), 8, (2, 1))]
+        );
+        assert_eq!(
+            rules.violations(&updates[5]),
+            [
+                violation((75, 13), 20, (2, 1)),
+                violation((29, 13), 8, (3, 1)),
+                violation((47, 13), 14, (4, 1)),
+                violation((47, 29), 19, (4, 3)),
+            ]
+        );
+
+        assert_eq!(
+            rules.violations(&updates[3])[0].to_string(),
+            "75 at position 1 comes before 97 at position 2, breaking rule 97|75 (line 16)"
+        );
+        assert_eq!(
+            rules.violations(&updates[4])[0].to_string(),
+            "13 at position 2 comes before 29 at position 3, breaking rule 29|13 (line 8)"
+        );
+    }
+
+    #[test]
+    fn cycles() {
+        let rules = rules(&[(1, 2), (2, 3), (3, 1), (1, 4)]);
+        let Err(OrderError::Cycle(cycle)) = rules.repair(&[4, 3, 2, 1]) else {
+            panic!("expected a cycle");
+        };
+        assert_eq!(cycle, [2, 3, 1]);
+        assert_eq!(
+            OrderError::Cycle(cycle).to_string(),
+            "cycle 2 -> 3 -> 1 -> 2"
+        );
+        // without page 2 the rules between the rest form a chain again
+        assert_eq!(rules.repair(&[4, 3, 1]), Ok(vec![3, 1, 4]));
+    }
+
+    #[test]
+    fn duplicate_pages() {
+        let rules = rules(&[(1, 2), (2, 3)]);
+        assert_eq!(rules.repair(&[3, 2, 3, 1]), Err(OrderError::Duplicate(3)));
+        assert_eq!(
+            OrderError::Duplicate(3).to_string(),
+            "page 3 appears more than once"
+        );
+    }
+
+    #[test]
+    fn ambiguous_pairs() {
+        let rules = rules(&[(1, 2), (1, 3)]);
+        assert_eq!(rules.repair(&[3, 2, 1]), Err(OrderError::Ambiguous(2, 3)));
+        assert_eq!(
+            OrderError::Ambiguous(2, 3).to_string(),
+            "no rule orders 2 and 3"
+        );
+        assert_eq!(rules.repair(&[2, 1]), Ok(vec![1, 2]));
+    }
+}
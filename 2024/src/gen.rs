@@ -0,0 +1,444 @@
+use std::ops::RangeInclusive;
+
+/// A small, seeded random number generator (SplitMix64), so the same seed always gives the
+/// same input.
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number from 0 up to but not including `n`.
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    pub fn range(&mut self, range: RangeInclusive<i64>) -> i64 {
+        let span = range.end().abs_diff(*range.start()) + 1;
+        range.start().wrapping_add((self.next_u64() % span) as i64)
+    }
+
+    /// True with probability `p`.
+    pub fn chance(&mut self, p: f64) -> bool {
+        ((self.next_u64() >> 11) as f64) < p * (1_u64 << 53) as f64
+    }
+
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+/// The size of each day's real inputs: lines, grid width, or characters.
+pub fn default_size(day: u32) -> usize {
+    match day {
+        1 | 2 | 5 => 1000,
+        3 => 18000,
+        4 | 10 => 50,
+        6 | 8 | 12 => 130,
+        7 => 850,
+        9 => 19999,
+        11 => 8,
+        13 => 320,
+        14 => 500,
+        _ => 100,
+    }
+}
+
+/// A random input for the day, or `None` if there's no generator for it.
+pub fn generate(day: u32, rng: &mut Rng, size: usize) -> Option<String> {
+    assert!(size > 0, "inputs need a size of at least 1");
+    Some(match day {
+        1 => location_lists(rng, size),
+        2 => reports(rng, size),
+        3 => corrupted_memory(rng, size),
+        4 => word_search(rng, size),
+        5 => print_queue(rng, size),
+        6 => lab(rng, size),
+        7 => equations(rng, size),
+        8 => antennas(rng, size),
+        9 => disk_map(rng, size),
+        10 => topographic_map(rng, size),
+        11 => stones(rng, size),
+        12 => garden(rng, size),
+        13 => claw_machines(rng, size).0,
+        14 => robots(rng, size),
+        _ => return None,
+    })
+}
+
+fn grid(rng: &mut Rng, size: usize, mut cell: impl FnMut(&mut Rng) -> char) -> Vec<Vec<char>> {
+    (0..size)
+        .map(|_| (0..size).map(|_| cell(rng)).collect())
+        .collect()
+}
+
+fn to_text(grid: &[Vec<char>]) -> String {
+    grid.iter()
+        .map(|row| row.iter().collect::<String>() + "\n")
+        .collect()
+}
+
+/// Day 1: two columns of location ids, with some ids on the right repeated.
+pub fn location_lists(rng: &mut Rng, size: usize) -> String {
+    let mut right: Vec<i64> = Vec::new();
+    let mut lines = String::new();
+    for _ in 0..size {
+        let left = rng.range(10000..=99999);
+        let r = match right.len() {
+            n if n > 0 && rng.chance(0.3) => right[rng.below(n)],
+            _ if rng.chance(0.2) => left,
+            _ => rng.range(10000..=99999),
+        };
+        right.push(r);
+        lines += &format!("{left}   {r}\n");
+    }
+    lines
+}
+
+/// Day 2: reports that mostly change steadily, some with a level or two out of line.
+pub fn reports(rng: &mut Rng, size: usize) -> String {
+    let mut lines = String::new();
+    for _ in 0..size {
+        let len = rng.below(4) + 5;
+        let direction = if rng.chance(0.5) { 1 } else { -1 };
+        let mut levels = vec![rng.range(30..=60)];
+        for _ in 1..len {
+            let step = rng.range(1..=3) * direction;
+            levels.push(levels[levels.len() - 1] + step);
+        }
+        for _ in 0..rng.below(3) {
+            let i = rng.below(len);
+            levels[i] += rng.range(-4..=4);
+        }
+        lines += &levels
+            .iter()
+            .map(|l| l.to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        lines.push('\n');
+    }
+    lines
+}
+
+/// Day 3: about `size` characters of noise with instructions and near misses mixed in.
+pub fn corrupted_memory(rng: &mut Rng, size: usize) -> String {
+    const NOISE: &[char] = &[
+        '%', '&', '*', '+', '!', '@', '^', '(', ')', '[', ']', '<', '>', ',', '?', ' ', 'm', 'u',
+        'l', 'd', 'o', 'n', '\'', 't', '{', '}', '#', '$', '/', ':', ';', '~',
+    ];
+    let mut memory = String::new();
+    while memory.len() < size {
+        let a = rng.range(1..=999);
+        let b = rng.range(1..=999);
+        match rng.below(20) {
+            0..=2 => memory += &format!("mul({a},{b})"),
+            3 => memory += "do()",
+            4 => memory += "don't()",
+            5 => memory += &format!("mul[{a},{b}]"),
+            6 => memory += &format!("mul({a}*"),
+            7 => memory += &format!("mul ( {a} , {b} )"),
+            8 if memory.len() % 3000 < 40 => memory.push('\n'),
+            _ => memory.push(*rng.pick(NOISE)),
+        }
+    }
+    memory.push('\n');
+    memory
+}
+
+/// Day 4: a square of the letters X, M, A and S.
+pub fn word_search(rng: &mut Rng, size: usize) -> String {
+    to_text(&grid(rng, size, |rng| *rng.pick(&['X', 'M', 'A', 'S'])))
+}
+
+/// Day 5: rules putting every pair of 49 pages in order, and `size` updates of an odd number
+/// of those pages, some of them already in order.
+pub fn print_queue(rng: &mut Rng, size: usize) -> String {
+    let mut pages: Vec<i64> = (10..=99).collect();
+    rng.shuffle(&mut pages);
+    pages.truncate(49);
+
+    let mut rules = Vec::new();
+    for i in 0..pages.len() {
+        for j in i + 1..pages.len() {
+            rules.push(format!("{}|{}", pages[i], pages[j]));
+        }
+    }
+    rng.shuffle(&mut rules);
+
+    let mut text = rules.join("\n") + "\n\n";
+    for _ in 0..size {
+        let len = rng.below(11) * 2 + 3;
+        let mut positions: Vec<usize> = (0..pages.len()).collect();
+        rng.shuffle(&mut positions);
+        positions.truncate(len);
+        if rng.chance(0.5) {
+            positions.sort();
+        }
+        let update: Vec<String> = positions.iter().map(|i| pages[*i].to_string()).collect();
+        text += &(update.join(",") + "\n");
+    }
+    text
+}
+
+/// Day 6: a square lab with scattered obstructions and the guard facing up.
+pub fn lab(rng: &mut Rng, size: usize) -> String {
+    let mut lab = grid(rng, size, |rng| if rng.chance(0.1) { '#' } else { '.' });
+    // the guard can stand on an obstruction, as the lab may not have anywhere else
+    let (x, y) = (rng.below(size), rng.below(size));
+    lab[y][x] = '^';
+    to_text(&lab)
+}
+
+/// Day 7: equations where about half can be made true with `+`, `*` and `||`.
+pub fn equations(rng: &mut Rng, size: usize) -> String {
+    let mut lines = String::new();
+    for _ in 0..size {
+        let values: Vec<u64> = (0..rng.below(7) + 2)
+            .map(|_| rng.range(1..=99) as u64)
+            .collect();
+        let test = if rng.chance(0.5) {
+            values[1..]
+                .iter()
+                .fold(values[0], |total, value| match rng.below(3) {
+                    0 => total + value,
+                    1 => total * value,
+                    _ => format!("{total}{value}").parse().unwrap(),
+                })
+        } else {
+            rng.range(1..=10_000_000) as u64
+        };
+        let values: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+        lines += &format!("{test}: {}\n", values.join(" "));
+    }
+    lines
+}
+
+/// Day 8: a square map with a few antennas of each of a handful of frequencies.
+pub fn antennas(rng: &mut Rng, size: usize) -> String {
+    let frequencies: Vec<char> = ('0'..='9').chain('a'..='z').chain('A'..='Z').collect();
+    let mut map = grid(rng, size, |_| '.');
+    for frequency in &frequencies[..(size / 3).clamp(1, frequencies.len())] {
+        for _ in 0..rng.below(3) + 2 {
+            let (x, y) = (rng.below(size), rng.below(size));
+            map[y][x] = *frequency;
+        }
+    }
+    to_text(&map)
+}
+
+/// Day 9: a disk map of `size` digits, or one more to make it end with a file.
+pub fn disk_map(rng: &mut Rng, size: usize) -> String {
+    let mut map: String = (0..size.max(1) | 1)
+        .map(|i| {
+            let digit = if i % 2 == 0 {
+                rng.range(1..=9)
+            } else {
+                rng.range(0..=9)
+            };
+            char::from(b'0' + digit as u8)
+        })
+        .collect();
+    map.push('\n');
+    map
+}
+
+/// Day 10: a square of random heights with some hiking trails walked into it.
+pub fn topographic_map(rng: &mut Rng, size: usize) -> String {
+    let mut map = grid(rng, size, |rng| char::from(b'0' + rng.below(10) as u8));
+    for _ in 0..size * size / 40 + 1 {
+        let mut trail = vec![(rng.below(size), rng.below(size))];
+        while trail.len() < 10 {
+            let (x, y) = trail[trail.len() - 1];
+            let next: Vec<(usize, usize)> = [
+                (x + 1, y),
+                (x, y + 1),
+                (x.wrapping_sub(1), y),
+                (x, y.wrapping_sub(1)),
+            ]
+            .into_iter()
+            .filter(|(x, y)| *x < size && *y < size && !trail.contains(&(*x, *y)))
+            .collect();
+            if next.is_empty() {
+                break;
+            }
+            trail.push(*rng.pick(&next));
+        }
+        for (height, (x, y)) in trail.into_iter().enumerate() {
+            map[y][x] = char::from(b'0' + height as u8);
+        }
+    }
+    to_text(&map)
+}
+
+/// Day 11: `size` stones, with a mix of short and long numbers on them.
+pub fn stones(rng: &mut Rng, size: usize) -> String {
+    let stones: Vec<String> = (0..size)
+        .map(|_| match rng.below(3) {
+            0 => rng.range(0..=9),
+            1 => rng.range(10..=9999),
+            _ => rng.range(10000..=9_999_999),
+        })
+        .map(|stone| stone.to_string())
+        .collect();
+    stones.join(" ") + "\n"
+}
+
+/// Day 12: a square garden of regions, each growing around a randomly placed seed plant.
+pub fn garden(rng: &mut Rng, size: usize) -> String {
+    let seeds: Vec<(usize, usize, char)> = (0..size * size / 20 + 1)
+        .map(|_| {
+            let plant = char::from(b'A' + rng.below(26) as u8);
+            (rng.below(size), rng.below(size), plant)
+        })
+        .collect();
+    let mut garden = grid(rng, size, |_| '.');
+    for (y, row) in garden.iter_mut().enumerate() {
+        for (x, cell) in row.iter_mut().enumerate() {
+            let nearest = seeds
+                .iter()
+                .min_by_key(|(sx, sy, _)| sx.abs_diff(x) + sy.abs_diff(y))
+                .unwrap();
+            *cell = nearest.2;
+        }
+    }
+    to_text(&garden)
+}
+
+/// Day 13: `size` claw machines, about half of which can be won within 100 presses of each
+/// button, along with the fewest tokens needed to win every prize possible for both parts.
+pub fn claw_machines(rng: &mut Rng, size: usize) -> (String, [u64; 2]) {
+    assert!(size > 0, "inputs need a size of at least 1");
+    let mut machines = Vec::new();
+    let mut tokens = [0, 0];
+    for _ in 0..size {
+        let (ax, ay, bx, by) = loop {
+            let buttons = (
+                rng.range(10..=99),
+                rng.range(10..=99),
+                rng.range(10..=99),
+                rng.range(10..=99),
+            );
+            if buttons.0 * buttons.3 != buttons.1 * buttons.2 {
+                break buttons;
+            }
+        };
+        let (px, py) = if rng.chance(0.5) {
+            let (a, b) = (rng.range(0..=100), rng.range(0..=100));
+            (a * ax + b * bx, a * ay + b * by)
+        } else {
+            (rng.range(1000..=20000), rng.range(1000..=20000))
+        };
+        for (part, offset) in [0, 10_000_000_000_000].into_iter().enumerate() {
+            if let Some((a, b)) = presses((ax, ay), (bx, by), (px + offset, py + offset)) {
+                if part == 1 || (a <= 100 && b <= 100) {
+                    tokens[part] += (3 * a + b) as u64;
+                }
+            }
+        }
+        machines.push(format!(
+            "Button A: X+{ax}, Y+{ay}\nButton B: X+{bx}, Y+{by}\nPrize: X={px}, Y={py}\n"
+        ));
+    }
+    (machines.join("\n"), tokens)
+}
+
+// the presses of each button that win the prize, if there's a whole number of each
+fn presses(a: (i64, i64), b: (i64, i64), prize: (i64, i64)) -> Option<(i64, i64)> {
+    let det = a.0 * b.1 - a.1 * b.0;
+    let a_presses = prize.0 * b.1 - prize.1 * b.0;
+    let b_presses = a.0 * prize.1 - a.1 * prize.0;
+    if a_presses % det != 0 || b_presses % det != 0 {
+        return None;
+    }
+    let (a_presses, b_presses) = (a_presses / det, b_presses / det);
+    (a_presses >= 0 && b_presses >= 0).then_some((a_presses, b_presses))
+}
+
+/// Day 14: `size` robots somewhere in the 101 by 103 space.
+pub fn robots(rng: &mut Rng, size: usize) -> String {
+    (0..size)
+        .map(|_| {
+            format!(
+                "p={},{} v={},{}\n",
+                rng.range(0..=100),
+                rng.range(0..=102),
+                rng.range(-100..=100),
+                rng.range(-100..=100)
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        day1::parse_lists,
+        parse::{key_values, parse_lines},
+        template::Template,
+    };
+
+    #[test]
+    fn same_seed_same_input() {
+        for day in 1..=14 {
+            let input = generate(day, &mut Rng::new(5), 12);
+            assert_eq!(input, generate(day, &mut Rng::new(5), 12));
+            assert!(input.is_some_and(|input| !input.is_empty()));
+        }
+        assert_ne!(
+            generate(6, &mut Rng::new(5), 12),
+            generate(6, &mut Rng::new(6), 12)
+        );
+    }
+
+    #[test]
+    fn labs_always_have_a_guard() {
+        for seed in 0..50 {
+            for size in 1..4 {
+                let lab = lab(&mut Rng::new(seed), size);
+                assert_eq!(lab.matches('^').count(), 1);
+                assert_eq!(lab.lines().count(), size);
+            }
+        }
+    }
+
+    #[test]
+    fn inputs_parse() {
+        let mut rng = Rng::new(1);
+        let (lists, errors) = parse_lists(&location_lists(&mut rng, 50));
+        assert_eq!((lists.left.len(), errors.len()), (50, 0));
+
+        let equations = parse_lines(&equations(&mut rng, 50), 1, key_values::<u64, u64>());
+        assert_eq!(equations.map(|equations| equations.len()), Ok(50));
+
+        let template = Template::new("p={px},{py} v={vx},{vy}");
+        assert_eq!(
+            template.records(&robots(&mut rng, 50), 1).map(|r| r.len()),
+            Ok(50)
+        );
+
+        let template = Template::new(
+            "Button A: X+{ax}, Y+{ay}\nButton B: X+{bx}, Y+{by}\nPrize: X={px}, Y={py}",
+        );
+        let (machines, _) = claw_machines(&mut rng, 50);
+        assert_eq!(template.records(&machines, 1).map(|r| r.len()), Ok(50));
+    }
+}
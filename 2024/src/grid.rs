@@ -0,0 +1,173 @@
+use std::{
+    fs,
+    ops::{Index, IndexMut},
+};
+
+/// A rectangular grid of cells, stored row by row. `(x, y)` is column `x` of row `y`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+}
+
+impl<T> Grid<T> {
+    /// Builds a grid from its rows. Panics if the rows aren't all the same length.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Self {
+        let width = rows.first().map_or(0, |row| row.len());
+        let height = rows.len();
+        assert!(
+            rows.iter().all(|row| row.len() == width),
+            "all rows of a grid must be the same length"
+        );
+        Self {
+            width,
+            height,
+            cells: rows.into_iter().flatten().collect(),
+        }
+    }
+
+    /// Builds a grid by calling `f` with the position of every cell.
+    pub fn from_fn(width: usize, height: usize, mut f: impl FnMut((usize, usize)) -> T) -> Self {
+        Self {
+            width,
+            height,
+            cells: (0..height)
+                .flat_map(|y| (0..width).map(move |x| (x, y)))
+                .map(&mut f)
+                .collect(),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+        if x < self.width && y < self.height {
+            Some(&self.cells[y * self.width + x])
+        } else {
+            None
+        }
+    }
+
+    /// The cell `steps` steps of `(dx, dy)` away from `(x, y)`, if that's still on the grid.
+    pub fn step(
+        &self,
+        (x, y): (usize, usize),
+        (dx, dy): (isize, isize),
+        steps: usize,
+    ) -> Option<(usize, usize)> {
+        let x = x.checked_add_signed(dx.checked_mul(steps as isize)?)?;
+        let y = y.checked_add_signed(dy.checked_mul(steps as isize)?)?;
+        (x < self.width && y < self.height).then_some((x, y))
+    }
+
+    /// Every position on the grid, row by row.
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width.max(1))
+    }
+}
+
+impl Grid<char> {
+    /// Reads a grid with one row per line. Blank lines are skipped.
+    pub fn parse(input: &str) -> Self {
+        Self::from_rows(
+            input
+                .lines()
+                .filter(|line| !line.is_empty())
+                .map(|line| line.chars().collect())
+                .collect(),
+        )
+    }
+
+    pub fn from_file(path: &str) -> Self {
+        let input =
+            fs::read_to_string(path).unwrap_or_else(|_| panic!("unable to read file {path}"));
+        Self::parse(&input)
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (x, y): (usize, usize)) -> &T {
+        self.get(x, y)
+            .unwrap_or_else(|| panic!("({x}, {y}) is outside the grid"))
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut T {
+        assert!(
+            x < self.width && y < self.height,
+            "({x}, {y}) is outside the grid"
+        );
+        &mut self.cells[y * self.width + x]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn non_square_grids() {
+        let grid = Grid::parse("abc\ndef\n");
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[(2, 1)], 'f');
+        assert_eq!(grid.get(1, 0), Some(&'b'));
+        assert_eq!(grid.get(3, 0), None);
+        assert_eq!(grid.get(0, 2), None);
+        assert_eq!(
+            grid.positions().collect::<Vec<_>>(),
+            [(0, 0), (1, 0), (2, 0), (0, 1), (1, 1), (2, 1)]
+        );
+        assert_eq!(
+            grid.rows().collect::<Vec<_>>(),
+            [['a', 'b', 'c'], ['d', 'e', 'f']]
+        );
+        assert_eq!(Grid::from_fn(3, 2, |(x, y)| grid[(x, y)]), grid);
+
+        let mut grid = Grid::new(1, 3, 0);
+        grid[(0, 2)] = 7;
+        assert_eq!(grid.rows().collect::<Vec<_>>(), [[0], [0], [7]]);
+    }
+
+    #[test]
+    fn steps() {
+        let grid = Grid::new(4, 2, ());
+        assert_eq!(grid.step((0, 0), (1, 1), 1), Some((1, 1)));
+        assert_eq!(grid.step((0, 0), (1, 1), 2), None);
+        assert_eq!(grid.step((0, 0), (1, 0), 3), Some((3, 0)));
+        assert_eq!(grid.step((0, 0), (1, 0), 4), None);
+        assert_eq!(grid.step((3, 1), (-1, -1), 1), Some((2, 0)));
+        assert_eq!(grid.step((3, 1), (-1, 0), 0), Some((3, 1)));
+        assert_eq!(grid.step((0, 1), (-1, 0), 1), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "all rows of a grid must be the same length")]
+    fn uneven_rows() {
+        Grid::parse("ab\nc");
+    }
+}
@@ -0,0 +1,12 @@
+pub mod day1;
+pub mod day2;
+pub mod day3;
+pub mod day5;
+pub mod gen;
+pub mod grid;
+pub mod parse;
+pub mod pattern;
+pub mod render;
+pub mod template;
+pub mod visualize;
+pub mod word_search;
@@ -0,0 +1,399 @@
+use std::{fmt, ops::RangeInclusive, str::FromStr};
+
+/// Where and why some input couldn't be parsed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    /// The line number, counting from 1.
+    pub line: usize,
+    /// The column, counting from 1.
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// The part of a line still to be parsed, and where it starts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Input<'a> {
+    pub rest: &'a str,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl<'a> Input<'a> {
+    pub fn new(text: &'a str, line: usize) -> Self {
+        Self {
+            rest: text,
+            line,
+            column: 1,
+        }
+    }
+
+    /// The input after its first `n` bytes.
+    pub fn advance(&self, n: usize) -> Self {
+        Self {
+            rest: &self.rest[n..],
+            line: self.line,
+            column: self.column + self.rest[..n].chars().count(),
+        }
+    }
+
+    pub fn error(&self, message: impl Into<String>) -> ParseError {
+        ParseError {
+            line: self.line,
+            column: self.column,
+            message: message.into(),
+        }
+    }
+
+    // what's next in the input, for error messages
+    pub(crate) fn found(&self) -> String {
+        match self.rest.chars().next() {
+            Some(c) => format!("'{c}'"),
+            None => "the end of the line".to_string(),
+        }
+    }
+}
+
+pub type ParseResult<'a, T> = Result<(T, Input<'a>), ParseError>;
+
+/// Something that reads a `T` off the front of the input, returning it and the input left.
+pub trait Parser<'a, T> {
+    fn parse(&self, input: Input<'a>) -> ParseResult<'a, T>;
+}
+
+impl<'a, T, F> Parser<'a, T> for F
+where
+    F: Fn(Input<'a>) -> ParseResult<'a, T>,
+{
+    fn parse(&self, input: Input<'a>) -> ParseResult<'a, T> {
+        self(input)
+    }
+}
+
+/// Exactly the text `expected`.
+pub fn tag<'a>(expected: &'static str) -> impl Parser<'a, &'a str> {
+    move |input: Input<'a>| {
+        if input.rest.starts_with(expected) {
+            Ok((&input.rest[..expected.len()], input.advance(expected.len())))
+        } else {
+            Err(input.error(format!("expected '{expected}', found {}", input.found())))
+        }
+    }
+}
+
+/// Any number of spaces and tabs, including none.
+pub fn spaces<'a>() -> impl Parser<'a, ()> {
+    |input: Input<'a>| {
+        let n = input.rest.len() - input.rest.trim_start_matches([' ', '\t']).len();
+        Ok(((), input.advance(n)))
+    }
+}
+
+/// At least one space or tab.
+pub fn spaces1<'a>() -> impl Parser<'a, ()> {
+    |input: Input<'a>| {
+        let n = input.rest.len() - input.rest.trim_start_matches([' ', '\t']).len();
+        if n == 0 {
+            return Err(input.error(format!("expected a space, found {}", input.found())));
+        }
+        Ok(((), input.advance(n)))
+    }
+}
+
+/// The text of a whole number, with a `-` in front if it's negative.
+pub fn number<'a>() -> impl Parser<'a, &'a str> {
+    |input: Input<'a>| {
+        let sign = usize::from(input.rest.starts_with('-'));
+        let digits = input.rest[sign..]
+            .bytes()
+            .take_while(|b| b.is_ascii_digit())
+            .count();
+        if digits == 0 {
+            return Err(input.error(format!("expected a number, found {}", input.found())));
+        }
+        Ok((&input.rest[..sign + digits], input.advance(sign + digits)))
+    }
+}
+
+/// A whole number, with a `-` in front if it's negative.
+pub fn int<'a, T: FromStr>() -> impl Parser<'a, T> {
+    |input: Input<'a>| {
+        let (text, rest) = number().parse(input)?;
+        let value = text
+            .parse()
+            .map_err(|_| input.error(format!("{text} is out of range")))?;
+        Ok((value, rest))
+    }
+}
+
+/// The end of the line, allowing for trailing spaces.
+pub fn end<'a>() -> impl Parser<'a, ()> {
+    |input: Input<'a>| {
+        let ((), input) = spaces().parse(input)?;
+        if input.rest.is_empty() {
+            Ok(((), input))
+        } else {
+            Err(input.error(format!(
+                "expected the end of the line, found {}",
+                input.found()
+            )))
+        }
+    }
+}
+
+pub fn map<'a, A, B>(parser: impl Parser<'a, A>, f: impl Fn(A) -> B) -> impl Parser<'a, B> {
+    move |input: Input<'a>| {
+        let (value, input) = parser.parse(input)?;
+        Ok((f(value), input))
+    }
+}
+
+pub fn pair<'a, A, B>(
+    first: impl Parser<'a, A>,
+    second: impl Parser<'a, B>,
+) -> impl Parser<'a, (A, B)> {
+    move |input: Input<'a>| {
+        let (a, input) = first.parse(input)?;
+        let (b, input) = second.parse(input)?;
+        Ok(((a, b), input))
+    }
+}
+
+/// `parser`, after `prefix`, which is thrown away.
+pub fn preceded<'a, A, B>(
+    prefix: impl Parser<'a, A>,
+    parser: impl Parser<'a, B>,
+) -> impl Parser<'a, B> {
+    map(pair(prefix, parser), |(_, b)| b)
+}
+
+/// `parser`, followed by `suffix`, which is thrown away.
+pub fn terminated<'a, A, B>(
+    parser: impl Parser<'a, A>,
+    suffix: impl Parser<'a, B>,
+) -> impl Parser<'a, A> {
+    map(pair(parser, suffix), |(a, _)| a)
+}
+
+/// One or more `item`s with a `separator` between each. A separator that isn't followed by
+/// another item is left in the input.
+pub fn separated<'a, T, S>(
+    item: impl Parser<'a, T>,
+    separator: impl Parser<'a, S>,
+) -> impl Parser<'a, Vec<T>> {
+    move |input: Input<'a>| {
+        let (first, mut input) = item.parse(input)?;
+        let mut items = vec![first];
+        while let Ok((next, rest)) = separator
+            .parse(input)
+            .and_then(|(_, rest)| item.parse(rest))
+        {
+            items.push(next);
+            input = rest;
+        }
+        Ok((items, input))
+    }
+}
+
+/// Runs `parser` over the whole of a line, which is line number `line` of the input.
+pub fn parse_line<'a, T>(
+    text: &'a str,
+    line: usize,
+    parser: impl Parser<'a, T>,
+) -> Result<T, ParseError> {
+    parse_whole(text, line, &parser)
+}
+
+fn parse_whole<'a, T>(
+    text: &'a str,
+    line: usize,
+    parser: &impl Parser<'a, T>,
+) -> Result<T, ParseError> {
+    let ((), input) = spaces().parse(Input::new(text, line))?;
+    let (value, input) = parser.parse(input)?;
+    end().parse(input)?;
+    Ok(value)
+}
+
+/// Runs `parser` over every line of `text`, numbering them from `first_line`. Blank lines
+/// are skipped.
+pub fn parse_lines<'a, T>(
+    text: &'a str,
+    first_line: usize,
+    parser: impl Parser<'a, T>,
+) -> Result<Vec<T>, ParseError> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| parse_whole(line, first_line + i, &parser))
+        .collect()
+}
+
+/// A number type that can be the bound of a range given on the command line.
+pub trait Bound: FromStr + Copy + PartialOrd {
+    const MAX: Self;
+    fn checked_pred(self) -> Option<Self>;
+}
+
+macro_rules! impl_bound {
+    ($($t:ty),*) => {$(
+        impl Bound for $t {
+            const MAX: Self = <$t>::MAX;
+            fn checked_pred(self) -> Option<Self> {
+                self.checked_sub(1)
+            }
+        }
+    )*};
+}
+
+impl_bound!(u32, u64, usize);
+
+/// Reads `a..b`, `a..=b`, `a..` (up to the largest value there is) or a single value `a` as
+/// an inclusive range. Anything else, including an empty range such as `5..5` or `3..0`, is
+/// `None`.
+pub fn range<T: Bound>(text: &str) -> Option<RangeInclusive<T>> {
+    let parse = |x: &str| x.parse::<T>().ok();
+    let range = if let Some((start, end)) = text.split_once("..=") {
+        parse(start)?..=parse(end)?
+    } else if let Some(start) = text.strip_suffix("..") {
+        parse(start)?..=T::MAX
+    } else if let Some((start, end)) = text.split_once("..") {
+        parse(start)?..=parse(end)?.checked_pred()?
+    } else {
+        parse(text)?..=parse(text)?
+    };
+    (range.start() <= range.end()).then_some(range)
+}
+
+/// Numbers separated by spaces, e.g. `7 6 4 2 1`.
+pub fn spaced<'a, T: FromStr>() -> impl Parser<'a, Vec<T>> {
+    separated(int(), spaces1())
+}
+
+/// Numbers separated by `separator`, e.g. `75,47,61` or `47|53`.
+pub fn delimited<'a, T: FromStr>(separator: &'static str) -> impl Parser<'a, Vec<T>> {
+    separated(int(), tag(separator))
+}
+
+/// A number, a colon and then some numbers separated by spaces, e.g. `190: 10 19`.
+pub fn key_values<'a, K: FromStr, V: FromStr>() -> impl Parser<'a, (K, Vec<V>)> {
+    pair(int(), preceded(pair(tag(":"), spaces()), spaced()))
+}
+
+/// A block of lines with a blank line before and after it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Section<'a> {
+    /// The number of the section's first line in the whole input, counting from 1.
+    pub line: usize,
+    pub text: &'a str,
+}
+
+impl<'a> Section<'a> {
+    /// The section's lines with their line numbers in the whole input.
+    pub fn lines(&self) -> impl Iterator<Item = (usize, &'a str)> {
+        let first = self.line;
+        self.text
+            .lines()
+            .enumerate()
+            .map(move |(i, line)| (first + i, line))
+    }
+
+    pub fn parse_lines<T>(&self, parser: impl Parser<'a, T>) -> Result<Vec<T>, ParseError> {
+        parse_lines(self.text, self.line, parser)
+    }
+}
+
+/// Splits the input at blank lines.
+pub fn sections(text: &str) -> Vec<Section<'_>> {
+    let mut sections = Vec::new();
+    let mut start: Option<(usize, usize)> = None;
+    let mut offset = 0;
+    for (i, line) in text.split_inclusive('\n').enumerate() {
+        let blank = line.trim().is_empty();
+        match (start, blank) {
+            (None, false) => start = Some((i + 1, offset)),
+            (Some((line, from)), true) => {
+                sections.push(Section {
+                    line,
+                    text: &text[from..offset],
+                });
+                start = None;
+            }
+            _ => {}
+        }
+        offset += line.len();
+    }
+    if let Some((line, from)) = start {
+        sections.push(Section {
+            line,
+            text: &text[from..],
+        });
+    }
+    sections
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_and_keys() {
+        assert_eq!(parse_line("3   4", 1, spaced::<u64>()), Ok(vec![3, 4]));
+        assert_eq!(parse_line("7 6 4  ", 1, spaced::<u64>()), Ok(vec![7, 6, 4]));
+        assert_eq!(
+            parse_line("75,47,61", 1, delimited::<u64>(",")),
+            Ok(vec![75, 47, 61])
+        );
+        assert_eq!(
+            parse_line("190: 10 19", 1, key_values::<u64, u64>()),
+            Ok((190, vec![10, 19]))
+        );
+    }
+
+    #[test]
+    fn errors_have_positions() {
+        let error = parse_lines("1 2\n\n3 x", 1, spaced::<u64>()).unwrap_err();
+        assert_eq!((error.line, error.column), (3, 3));
+        assert_eq!(error.message, "expected the end of the line, found 'x'");
+
+        let error = parse_line("47|53|", 5, delimited::<u64>("|")).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 5, column 6: expected the end of the line, found '|'"
+        );
+    }
+
+    #[test]
+    fn blank_line_sections() {
+        let text = "47|53\n97|13\n\n\n75,47\n";
+        let sections = sections(text);
+        assert_eq!(sections.len(), 2);
+        assert_eq!((sections[0].line, sections[0].text), (1, "47|53\n97|13\n"));
+        assert_eq!((sections[1].line, sections[1].text), (5, "75,47\n"));
+        let error = sections[1].parse_lines(spaced::<u64>()).unwrap_err();
+        assert_eq!((error.line, error.column), (5, 3));
+    }
+
+    #[test]
+    fn ranges() {
+        assert_eq!(range::<u32>("3..7"), Some(3..=6));
+        assert_eq!(range::<u32>("3..=7"), Some(3..=7));
+        assert_eq!(range::<u64>("4"), Some(4..=4));
+        assert_eq!(range::<u32>("2.."), Some(2..=u32::MAX));
+        assert_eq!(range::<u32>("3..0"), None);
+        assert_eq!(range::<u32>("0..0"), None);
+        assert_eq!(range::<u32>("5..=4"), None);
+        assert_eq!(range::<u32>("a..3"), None);
+    }
+}
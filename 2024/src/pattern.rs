@@ -0,0 +1,313 @@
+use crate::grid::Grid;
+
+// modulus and base of the polynomial row hashes
+const MODULUS: u64 = (1 << 61) - 1;
+const BASE: u64 = 1_000_003;
+
+/// A small 2D pattern (a stencil) to look for in a grid. Cells set to `None` match anything.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mask {
+    cells: Grid<Option<char>>,
+}
+
+/// One of the eight ways a mask can be turned over and around: reflected left to right (or
+/// not), then turned clockwise by a number of quarter turns.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Orientation {
+    pub reflected: bool,
+    pub quarter_turns: u8,
+}
+
+/// A place a mask matched, and which way round it was.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PatternMatch {
+    /// The top left corner of the (turned) mask on the grid.
+    pub position: (usize, usize),
+    pub orientation: Orientation,
+}
+
+impl Mask {
+    /// Builds a mask from its rows, with `wildcard` standing for any letter.
+    pub fn parse(rows: &[&str], wildcard: char) -> Self {
+        Self {
+            cells: Grid::from_rows(
+                rows.iter()
+                    .map(|row| row.chars().map(|c| (c != wildcard).then_some(c)).collect())
+                    .collect(),
+            ),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.cells.width()
+    }
+
+    pub fn height(&self) -> usize {
+        self.cells.height()
+    }
+
+    /// Whether the mask matches the grid with its top left corner at `(x, y)`.
+    pub fn matches_at(&self, grid: &Grid<char>, (x, y): (usize, usize)) -> bool {
+        self.cells
+            .positions()
+            .all(|(mx, my)| match self.cells[(mx, my)] {
+                None => true,
+                Some(c) => grid.get(x + mx, y + my) == Some(&c),
+            })
+    }
+
+    /// The mask turned a quarter turn clockwise.
+    pub fn rotate(&self) -> Self {
+        let height = self.height();
+        Self {
+            cells: Grid::from_fn(height, self.width(), |(x, y)| {
+                self.cells[(y, height - 1 - x)]
+            }),
+        }
+    }
+
+    /// The mask mirrored left to right.
+    pub fn reflect(&self) -> Self {
+        let width = self.width();
+        Self {
+            cells: Grid::from_fn(width, self.height(), |(x, y)| {
+                self.cells[(width - 1 - x, y)]
+            }),
+        }
+    }
+
+    pub fn oriented(&self, orientation: Orientation) -> Self {
+        let mut mask = if orientation.reflected {
+            self.reflect()
+        } else {
+            self.clone()
+        };
+        for _ in 0..orientation.quarter_turns % 4 {
+            mask = mask.rotate();
+        }
+        mask
+    }
+
+    /// Every different way the mask looks when turned over and around. A symmetric mask
+    /// has fewer than eight.
+    pub fn symmetries(&self) -> Vec<(Orientation, Mask)> {
+        let mut symmetries: Vec<(Orientation, Mask)> = Vec::new();
+        for reflected in [false, true] {
+            for quarter_turns in 0..4 {
+                let orientation = Orientation {
+                    reflected,
+                    quarter_turns,
+                };
+                let mask = self.oriented(orientation);
+                if symmetries.iter().all(|(_, m)| *m != mask) {
+                    symmetries.push((orientation, mask));
+                }
+            }
+        }
+        symmetries
+    }
+
+    // the horizontal runs of cells that aren't wildcards, as (x, y, letters)
+    fn runs(&self) -> Vec<(usize, usize, Vec<char>)> {
+        let mut runs = Vec::new();
+        for (y, row) in self.cells.rows().enumerate() {
+            let mut x = 0;
+            while x < row.len() {
+                if row[x].is_none() {
+                    x += 1;
+                    continue;
+                }
+                let start = x;
+                while x < row.len() && row[x].is_some() {
+                    x += 1;
+                }
+                runs.push((start, y, row[start..x].iter().flatten().copied().collect()));
+            }
+        }
+        runs
+    }
+}
+
+/// Finds masks in a grid.
+///
+/// Every row of the grid is hashed once up front, so any stretch of a row can be compared
+/// against a run of letters in the mask in constant time. Each mask is first looked for by
+/// its longest run alone, and only the places that run matches are checked any further.
+pub struct Matcher<'a> {
+    grid: &'a Grid<char>,
+    // prefix hashes of each row, one more than the grid is wide
+    prefixes: Grid<u64>,
+    powers: Vec<u64>,
+}
+
+impl<'a> Matcher<'a> {
+    pub fn new(grid: &'a Grid<char>) -> Self {
+        let mut powers = vec![1; grid.width() + 1];
+        for i in 1..powers.len() {
+            powers[i] = mul_mod(powers[i - 1], BASE);
+        }
+        let mut prefixes = Grid::new(grid.width() + 1, grid.height(), 0);
+        for y in 0..grid.height() {
+            for x in 0..grid.width() {
+                prefixes[(x + 1, y)] =
+                    (mul_mod(prefixes[(x, y)], BASE) + letter_hash(grid[(x, y)])) % MODULUS;
+            }
+        }
+        Self {
+            grid,
+            prefixes,
+            powers,
+        }
+    }
+
+    // hash of the `len` letters of row `y` starting at column `x`
+    fn row_hash(&self, x: usize, y: usize, len: usize) -> u64 {
+        let whole = self.prefixes[(x + len, y)];
+        let before = mul_mod(self.prefixes[(x, y)], self.powers[len]);
+        (whole + MODULUS - before) % MODULUS
+    }
+
+    /// The top left corner of every place the mask matches, as given.
+    pub fn find(&self, mask: &Mask) -> Vec<(usize, usize)> {
+        if mask.width() > self.grid.width() || mask.height() > self.grid.height() {
+            return Vec::new();
+        }
+        let positions = (0..=self.grid.height() - mask.height())
+            .flat_map(move |y| (0..=self.grid.width() - mask.width()).map(move |x| (x, y)));
+
+        let runs: Vec<(usize, usize, usize, u64)> = mask
+            .runs()
+            .into_iter()
+            .map(|(x, y, letters)| (x, y, letters.len(), hash_letters(&letters)))
+            .collect();
+        let Some(anchor) = runs.iter().max_by_key(|run| run.2) else {
+            // a mask of nothing but wildcards matches everywhere it fits
+            return positions.collect();
+        };
+
+        let run_matches =
+            |(x, y): (usize, usize), (dx, dy, len, hash): (usize, usize, usize, u64)| {
+                self.row_hash(x + dx, y + dy, len) == hash
+            };
+        positions
+            .filter(|position| run_matches(*position, *anchor))
+            .filter(|position| runs.iter().all(|run| run_matches(*position, *run)))
+            // rule out hash collisions
+            .filter(|position| mask.matches_at(self.grid, *position))
+            .collect()
+    }
+
+    /// Every place the mask matches, turned over and around any way.
+    pub fn find_any_orientation(&self, mask: &Mask) -> Vec<PatternMatch> {
+        mask.symmetries()
+            .into_iter()
+            .flat_map(|(orientation, mask)| {
+                self.find(&mask)
+                    .into_iter()
+                    .map(move |position| PatternMatch {
+                        position,
+                        orientation,
+                    })
+            })
+            .collect()
+    }
+}
+
+fn letter_hash(c: char) -> u64 {
+    c as u64 + 1
+}
+
+fn hash_letters(letters: &[char]) -> u64 {
+    letters.iter().fold(0, |hash, c| {
+        (mul_mod(hash, BASE) + letter_hash(*c)) % MODULUS
+    })
+}
+
+fn mul_mod(a: u64, b: u64) -> u64 {
+    ((a as u128 * b as u128) % MODULUS as u128) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn rows(
+        alphabet: &'static [char],
+        width: usize,
+        height: usize,
+    ) -> impl Strategy<Value = Vec<String>> {
+        prop::collection::vec(
+            prop::collection::vec(prop::sample::select(alphabet), width)
+                .prop_map(|row| row.into_iter().collect()),
+            height,
+        )
+    }
+
+    fn grid_and_mask() -> impl Strategy<Value = (Vec<String>, Vec<String>)> {
+        (1_usize..9, 1_usize..9, 1_usize..4, 1_usize..4).prop_flat_map(|(w, h, mw, mh)| {
+            (rows(&['A', 'B'], w, h), rows(&['A', 'B', '.'], mw, mh))
+        })
+    }
+
+    // every position the mask fits, checked cell by cell
+    fn naive_find(grid: &Grid<char>, mask: &Mask) -> Vec<(usize, usize)> {
+        grid.positions()
+            .filter(|(x, y)| x + mask.width() <= grid.width() && y + mask.height() <= grid.height())
+            .filter(|position| mask.matches_at(grid, *position))
+            .collect()
+    }
+
+    #[test]
+    fn find() {
+        let grid = Grid::parse("ABAB\nBABA\nABAB");
+        let matcher = Matcher::new(&grid);
+        assert_eq!(
+            matcher.find(&Mask::parse(&["AB", ".A"], '.')),
+            [(0, 0), (2, 0), (1, 1)]
+        );
+        assert_eq!(matcher.find(&Mask::parse(&["BB"], '.')), []);
+        // masks bigger than the grid don't fit anywhere, wildcards fit everywhere they fit
+        assert_eq!(matcher.find(&Mask::parse(&["A....", "....."], '.')), []);
+        assert_eq!(
+            matcher.find(&Mask::parse(&["..", "..", ".."], '.')),
+            [(0, 0), (1, 0), (2, 0)]
+        );
+    }
+
+    #[test]
+    fn other_wildcards() {
+        let grid = Grid::parse("a.b\nc.d");
+        let matcher = Matcher::new(&grid);
+        // `.` is a letter like any other once something else is the wildcard
+        assert_eq!(matcher.find(&Mask::parse(&["?.", "?."], '?')), [(0, 0)]);
+        assert_eq!(matcher.find(&Mask::parse(&[".?"], '?')), [(1, 0), (1, 1)]);
+        assert_eq!(
+            Mask::parse(&["x?"], '?').rotate(),
+            Mask::parse(&["x", "?"], '?')
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn hashed_search_agrees_with_naive((grid, mask) in grid_and_mask()) {
+            let grid = Grid::parse(&grid.join("\n"));
+            let mask = Mask::parse(&mask.iter().map(|row| row.as_str()).collect::<Vec<_>>(), '.');
+            let matcher = Matcher::new(&grid);
+
+            let mut found = matcher.find(&mask);
+            found.sort();
+            let mut expected = naive_find(&grid, &mask);
+            expected.sort();
+            prop_assert_eq!(found, expected);
+
+            let oriented = matcher.find_any_orientation(&mask).len();
+            let expected: usize = mask
+                .symmetries()
+                .iter()
+                .map(|(_, mask)| naive_find(&grid, mask).len())
+                .sum();
+            prop_assert_eq!(oriented, expected);
+        }
+    }
+}
@@ -0,0 +1,385 @@
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter},
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+};
+
+use image::RgbImage;
+
+// number of robots (or whatever is being counted) at which the heat map saturates
+const HEAT_LEVELS: u32 = 8;
+
+/// A single simulation frame, stored as the number of entities occupying each cell.
+#[derive(Clone, Debug)]
+pub struct Frame {
+    width: u32,
+    height: u32,
+    density: Vec<u32>,
+}
+
+impl Frame {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            density: vec![0; (width * height) as usize],
+        }
+    }
+
+    /// Builds a frame in a single pass over the given points. Points outside the frame are ignored.
+    pub fn from_points<I>(width: u32, height: u32, points: I) -> Self
+    where
+        I: IntoIterator<Item = (i64, i64)>,
+    {
+        let mut frame = Self::new(width, height);
+        for (x, y) in points {
+            if x >= 0 && y >= 0 && (x as u32) < width && (y as u32) < height {
+                frame.add(x as u32, y as u32);
+            }
+        }
+        frame
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn add(&mut self, x: u32, y: u32) {
+        self.density[(y * self.width + x) as usize] += 1;
+    }
+
+    pub fn density(&self, x: u32, y: u32) -> u32 {
+        self.density[(y * self.width + x) as usize]
+    }
+
+    // palette indices for every pixel of the frame, with each cell blown up to a scale x scale block
+    fn indexed_pixels(&self, scale: u32, color_map: ColorMap) -> Vec<u8> {
+        let scaled_width = self.width * scale;
+        let mut pixels = Vec::with_capacity((scaled_width * self.height * scale) as usize);
+        for row in self.density.chunks(self.width as usize) {
+            let scaled_row = row
+                .iter()
+                .flat_map(|d| std::iter::repeat_n(color_map.index(*d), scale as usize))
+                .collect::<Vec<_>>();
+            for _ in 0..scale {
+                pixels.extend_from_slice(&scaled_row);
+            }
+        }
+        pixels
+    }
+
+    pub fn to_image(&self, scale: u32, color_map: ColorMap) -> RgbImage {
+        let palette = color_map.palette();
+        let pixels = self
+            .indexed_pixels(scale, color_map)
+            .into_iter()
+            .flat_map(|i| palette[i as usize])
+            .collect();
+        RgbImage::from_raw(self.width * scale, self.height * scale, pixels).unwrap()
+    }
+}
+
+/// How cell densities are turned into colours.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorMap {
+    /// white for occupied cells, black for empty ones
+    Mono,
+    /// black through red and yellow to white as more entities share a cell
+    Heat,
+}
+
+impl ColorMap {
+    pub fn palette(&self) -> Vec<[u8; 3]> {
+        match self {
+            ColorMap::Mono => vec![[0, 0, 0], [255, 255, 255]],
+            ColorMap::Heat => (0..=HEAT_LEVELS)
+                .map(|level| {
+                    let t = level as f64 / HEAT_LEVELS as f64;
+                    let r = (t * 3.0).min(1.0);
+                    let g = (t * 3.0 - 1.0).clamp(0.0, 1.0);
+                    let b = (t * 3.0 - 2.0).clamp(0.0, 1.0);
+                    [(r * 255.0) as u8, (g * 255.0) as u8, (b * 255.0) as u8]
+                })
+                .collect(),
+        }
+    }
+
+    pub fn index(&self, density: u32) -> u8 {
+        match self {
+            ColorMap::Mono => density.min(1) as u8,
+            ColorMap::Heat => density.min(HEAT_LEVELS) as u8,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    PngSequence,
+    Gif,
+    Apng,
+}
+
+impl Format {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "png" => Some(Format::PngSequence),
+            "gif" => Some(Format::Gif),
+            "apng" => Some(Format::Apng),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct RenderOptions {
+    pub scale: u32,
+    pub color_map: ColorMap,
+    pub frame_delay_ms: u16,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            scale: 1,
+            color_map: ColorMap::Mono,
+            frame_delay_ms: 100,
+        }
+    }
+}
+
+/// Somewhere to send frames as a simulation runs.
+pub trait FrameSink {
+    fn write_frame(&mut self, frame: &Frame) -> io::Result<()>;
+
+    /// Flushes anything still buffered. No frames may be written afterwards.
+    fn finish(&mut self) -> io::Result<()>;
+}
+
+/// Opens a sink for the frames numbered `frames`, writing to `path` and creating any missing
+/// parent directories. For a PNG sequence `path` is the directory the numbered frames go in.
+/// The frame count is needed up front because APNG stores it in the file header.
+pub fn open_sink(
+    path: impl AsRef<Path>,
+    format: Format,
+    frames: RangeInclusive<u32>,
+    width: u32,
+    height: u32,
+    options: RenderOptions,
+) -> io::Result<Box<dyn FrameSink>> {
+    let path = path.as_ref();
+    let frame_count = frames.clone().count() as u32;
+    let sink: Box<dyn FrameSink> = match format {
+        Format::PngSequence => Box::new(PngSequenceSink::new(path, *frames.start(), options)?),
+        Format::Gif => Box::new(GifSink::new(create_file(path)?, width, height, options)?),
+        Format::Apng => Box::new(ApngSink::new(
+            create_file(path)?,
+            frame_count,
+            width,
+            height,
+            options,
+        )?),
+    };
+    Ok(sink)
+}
+
+fn create_file(path: &Path) -> io::Result<BufWriter<File>> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    Ok(BufWriter::new(File::create(path)?))
+}
+
+/// Writes every frame to its own numbered PNG file.
+pub struct PngSequenceSink {
+    dir: PathBuf,
+    next_index: u32,
+    options: RenderOptions,
+}
+
+impl PngSequenceSink {
+    pub fn new(dir: &Path, first_index: u32, options: RenderOptions) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            next_index: first_index,
+            options,
+        })
+    }
+}
+
+impl FrameSink for PngSequenceSink {
+    fn write_frame(&mut self, frame: &Frame) -> io::Result<()> {
+        let path = self.dir.join(format!("{}.png", self.next_index));
+        frame
+            .to_image(self.options.scale, self.options.color_map)
+            .save(path)
+            .map_err(io::Error::other)?;
+        self.next_index += 1;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+pub struct GifSink {
+    encoder: Option<gif::Encoder<BufWriter<File>>>,
+    width: u16,
+    height: u16,
+    options: RenderOptions,
+}
+
+impl GifSink {
+    pub fn new(
+        writer: BufWriter<File>,
+        width: u32,
+        height: u32,
+        options: RenderOptions,
+    ) -> io::Result<Self> {
+        let (width, height) = scaled_dimensions(width, height, options.scale)?;
+        let palette = options.color_map.palette().concat();
+        let mut encoder =
+            gif::Encoder::new(writer, width, height, &palette).map_err(io::Error::other)?;
+        encoder
+            .set_repeat(gif::Repeat::Infinite)
+            .map_err(io::Error::other)?;
+        Ok(Self {
+            encoder: Some(encoder),
+            width,
+            height,
+            options,
+        })
+    }
+}
+
+impl FrameSink for GifSink {
+    fn write_frame(&mut self, frame: &Frame) -> io::Result<()> {
+        let encoder = self
+            .encoder
+            .as_mut()
+            .ok_or_else(|| io::Error::other("gif sink already finished"))?;
+        let pixels = frame.indexed_pixels(self.options.scale, self.options.color_map);
+        let mut gif_frame = gif::Frame::from_indexed_pixels(self.width, self.height, pixels, None);
+        // gif delays are in hundredths of a second
+        gif_frame.delay = self.options.frame_delay_ms / 10;
+        encoder.write_frame(&gif_frame).map_err(io::Error::other)
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        // the gif trailer is written when the encoder is dropped
+        if let Some(encoder) = self.encoder.take() {
+            encoder.into_inner().map_err(io::Error::other)?;
+        }
+        Ok(())
+    }
+}
+
+pub struct ApngSink {
+    writer: Option<png::Writer<BufWriter<File>>>,
+    options: RenderOptions,
+}
+
+impl ApngSink {
+    pub fn new(
+        writer: BufWriter<File>,
+        frame_count: u32,
+        width: u32,
+        height: u32,
+        options: RenderOptions,
+    ) -> io::Result<Self> {
+        let mut encoder = png::Encoder::new(writer, width * options.scale, height * options.scale);
+        encoder.set_color(png::ColorType::Indexed);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_palette(options.color_map.palette().concat());
+        encoder.set_animated(frame_count, 0)?;
+        encoder.set_frame_delay(options.frame_delay_ms, 1000)?;
+        Ok(Self {
+            writer: Some(encoder.write_header()?),
+            options,
+        })
+    }
+}
+
+impl FrameSink for ApngSink {
+    fn write_frame(&mut self, frame: &Frame) -> io::Result<()> {
+        let writer = self
+            .writer
+            .as_mut()
+            .ok_or_else(|| io::Error::other("apng sink already finished"))?;
+        let pixels = frame.indexed_pixels(self.options.scale, self.options.color_map);
+        writer.write_image_data(&pixels)?;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        if let Some(writer) = self.writer.take() {
+            writer.finish()?;
+        }
+        Ok(())
+    }
+}
+
+fn scaled_dimensions(width: u32, height: u32, scale: u32) -> io::Result<(u16, u16)> {
+    let to_u16 = |v: u32| {
+        u16::try_from(v * scale)
+            .map_err(|_| io::Error::other(format!("scaled dimension {} too large", v * scale)))
+    };
+    Ok((to_u16(width)?, to_u16(height)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_from_points() {
+        let frame = Frame::from_points(3, 2, [(0, 0), (2, 1), (2, 1), (-1, 0), (3, 0), (0, 2)]);
+        assert_eq!((frame.width(), frame.height()), (3, 2));
+        assert_eq!(frame.density(0, 0), 1);
+        assert_eq!(frame.density(2, 1), 2);
+        assert_eq!(frame.density(1, 0), 0);
+        let total: u32 = (0..2)
+            .flat_map(|y| (0..3).map(move |x| (x, y)))
+            .map(|(x, y)| frame.density(x, y))
+            .sum();
+        assert_eq!(total, 3);
+    }
+
+    #[test]
+    fn scaled_pixels() {
+        let frame = Frame::from_points(2, 1, [(1, 0), (1, 0), (1, 0)]);
+        assert_eq!(frame.indexed_pixels(1, ColorMap::Heat), vec![0, 3]);
+        assert_eq!(
+            frame.indexed_pixels(2, ColorMap::Mono),
+            vec![0, 0, 1, 1, 0, 0, 1, 1]
+        );
+        let image = frame.to_image(3, ColorMap::Mono);
+        assert_eq!(image.dimensions(), (6, 3));
+        assert_eq!(image.get_pixel(5, 2).0, [255, 255, 255]);
+        assert_eq!(image.get_pixel(0, 0).0, [0, 0, 0]);
+    }
+
+    #[test]
+    fn color_maps() {
+        assert_eq!(ColorMap::Mono.index(0), 0);
+        assert_eq!(ColorMap::Mono.index(7), 1);
+        assert_eq!(ColorMap::Heat.index(5), 5);
+        assert_eq!(ColorMap::Heat.index(100), HEAT_LEVELS as u8);
+
+        let heat = ColorMap::Heat.palette();
+        assert_eq!(heat.len(), HEAT_LEVELS as usize + 1);
+        assert_eq!(heat[0], [0, 0, 0]);
+        assert_eq!(heat[HEAT_LEVELS as usize], [255, 255, 255]);
+        // every entry is at least as bright as the one before
+        assert!(heat
+            .windows(2)
+            .all(|w| w[0].iter().zip(&w[1]).all(|(a, b)| a <= b)));
+        assert_eq!(ColorMap::Mono.palette(), vec![[0, 0, 0], [255, 255, 255]]);
+    }
+}
@@ -0,0 +1,425 @@
+use std::str::FromStr;
+
+use crate::parse::{
+    end, number, sections, separated, spaces, spaces1, Input, ParseError, ParseResult, Parser,
+};
+
+/// What a field in a template matches.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Kind {
+    /// A whole number, written `{name}`.
+    Number,
+    /// Numbers separated by spaces, written `{name:list}`, or by some other text, e.g.
+    /// `{name:list,}`.
+    List(Option<String>),
+    /// Anything up to the next part of the template or the end of the line, written
+    /// `{name:text}`.
+    Text,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Piece {
+    Literal(String),
+    // any run of spaces in the template, which matches one or more spaces or tabs
+    Spaces,
+    // a field, by its position in `Template::fields`
+    Field(usize),
+}
+
+/// A description of some input as the text it should hold, with a `{name}` in place of each
+/// value, e.g. `Button A: X+{ax}, Y+{ay}`. A template of several lines describes records
+/// that span that many lines.
+///
+/// Input that doesn't fit the template is an error, giving the line and column it stops
+/// fitting at.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Template {
+    source: Vec<String>,
+    lines: Vec<Vec<Piece>>,
+    fields: Vec<(String, Kind)>,
+}
+
+impl Template {
+    pub fn new(template: &str) -> Self {
+        let mut parsed = Self {
+            source: Vec::new(),
+            lines: Vec::new(),
+            fields: Vec::new(),
+        };
+        for line in template.lines() {
+            assert!(!line.trim().is_empty(), "template lines can't be blank");
+            let pieces = parsed.parse_line(line);
+            parsed.source.push(line.to_string());
+            parsed.lines.push(pieces);
+        }
+        assert!(!parsed.lines.is_empty(), "template is empty");
+        parsed
+    }
+
+    fn parse_line(&mut self, line: &str) -> Vec<Piece> {
+        let mut pieces = Vec::new();
+        let mut rest = line.trim();
+        while !rest.is_empty() {
+            if let Some(field) = rest.strip_prefix('{') {
+                let (field, after) = field
+                    .split_once('}')
+                    .unwrap_or_else(|| panic!("unclosed field in template '{line}'"));
+                let (name, kind) = field.split_once(':').unwrap_or((field, ""));
+                let kind = match kind {
+                    "" => Kind::Number,
+                    "list" => Kind::List(None),
+                    "text" => Kind::Text,
+                    _ => match kind.strip_prefix("list") {
+                        Some(separator) => Kind::List(Some(separator.to_string())),
+                        None => panic!("unknown kind of field '{kind}' in template '{line}'"),
+                    },
+                };
+                assert!(
+                    self.fields.iter().all(|(other, _)| other != name),
+                    "field {name} is in the template twice"
+                );
+                if let Some(Piece::Field(_)) = pieces.last() {
+                    panic!("field {name} needs something between it and the field before it");
+                }
+                pieces.push(Piece::Field(self.fields.len()));
+                self.fields.push((name.to_string(), kind));
+                rest = after;
+            } else if rest.starts_with([' ', '\t']) {
+                pieces.push(Piece::Spaces);
+                rest = rest.trim_start();
+            } else {
+                let len = rest.find([' ', '\t', '{']).unwrap_or(rest.len());
+                pieces.push(Piece::Literal(rest[..len].to_string()));
+                rest = &rest[len..];
+            }
+        }
+        pieces
+    }
+
+    /// The names of the template's fields, in the order they appear.
+    pub fn fields(&self) -> impl Iterator<Item = &str> {
+        self.fields.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// Matches every record in `text`, whose first line is line `first_line` of the input.
+    /// Blank lines between records are skipped, but a record can't have one in the middle.
+    pub fn records<'a>(
+        &self,
+        text: &'a str,
+        first_line: usize,
+    ) -> Result<Vec<Record<'_, 'a>>, ParseError> {
+        let height = self.lines.len();
+        let mut records = Vec::new();
+        for section in sections(text) {
+            let lines: Vec<(usize, &str)> = section
+                .lines()
+                .map(|(line, text)| (first_line - 1 + line, text))
+                .collect();
+            for chunk in lines.chunks(height) {
+                let mut values = Vec::with_capacity(self.fields.len());
+                for ((line, text), pieces) in chunk.iter().zip(&self.lines) {
+                    self.match_line(pieces, text, *line, &mut values)?;
+                }
+                if chunk.len() < height {
+                    let (line, _) = chunk[chunk.len() - 1];
+                    return Err(ParseError {
+                        line: line + 1,
+                        column: 1,
+                        message: format!(
+                            "expected a line like '{}', found a blank line or the end of the input",
+                            self.source[chunk.len()]
+                        ),
+                    });
+                }
+                records.push(Record {
+                    template: self,
+                    values,
+                });
+            }
+        }
+        Ok(records)
+    }
+
+    fn match_line<'a>(
+        &self,
+        pieces: &[Piece],
+        text: &'a str,
+        line: usize,
+        values: &mut Vec<Value<'a>>,
+    ) -> Result<(), ParseError> {
+        let ((), mut input) = spaces().parse(Input::new(text, line))?;
+        for (i, piece) in pieces.iter().enumerate() {
+            input = match piece {
+                Piece::Literal(literal) => {
+                    if !input.rest.starts_with(literal.as_str()) {
+                        return Err(
+                            input.error(format!("expected '{literal}', found {}", input.found()))
+                        );
+                    }
+                    input.advance(literal.len())
+                }
+                Piece::Spaces => spaces1().parse(input)?.1,
+                Piece::Field(field) => {
+                    let (items, rest) =
+                        match_field(&self.fields[*field].1, pieces.get(i + 1), input)?;
+                    values.push(Value { line, items });
+                    rest
+                }
+            };
+        }
+        end().parse(input)?;
+        Ok(())
+    }
+}
+
+// matches a field at the start of the input, returning its items with the columns they
+// start at
+fn match_field<'a>(
+    kind: &Kind,
+    next: Option<&Piece>,
+    input: Input<'a>,
+) -> ParseResult<'a, Vec<(&'a str, usize)>> {
+    let item = |input: Input<'a>| {
+        let (text, rest) = number().parse(input)?;
+        Ok(((text, input.column), rest))
+    };
+    match kind {
+        Kind::Number => {
+            let (item, rest) = item(input)?;
+            Ok((vec![item], rest))
+        }
+        Kind::List(None) => separated(item, spaces1()).parse(input),
+        Kind::List(Some(separator)) => {
+            let separator = |input: Input<'a>| {
+                if input.rest.starts_with(separator.as_str()) {
+                    Ok(((), input.advance(separator.len())))
+                } else {
+                    Err(input.error(format!("expected '{separator}'")))
+                }
+            };
+            separated(item, separator).parse(input)
+        }
+        Kind::Text => {
+            let len = match next {
+                Some(Piece::Literal(literal)) => input.rest.find(literal.as_str()),
+                Some(Piece::Spaces) => input.rest.find([' ', '\t']),
+                _ => None,
+            }
+            .unwrap_or(input.rest.trim_end().len());
+            if len == 0 {
+                return Err(input.error(format!("expected some text, found {}", input.found())));
+            }
+            Ok((vec![(&input.rest[..len], input.column)], input.advance(len)))
+        }
+    }
+}
+
+// the text a field matched
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Value<'a> {
+    line: usize,
+    items: Vec<(&'a str, usize)>,
+}
+
+/// The values of the fields in one match of a template.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Record<'t, 'a> {
+    template: &'t Template,
+    values: Vec<Value<'a>>,
+}
+
+impl<'a> Record<'_, 'a> {
+    fn value(&self, name: &str) -> (&Kind, &Value<'a>) {
+        let field = self
+            .template
+            .fields
+            .iter()
+            .position(|(field, _)| field == name)
+            .unwrap_or_else(|| panic!("there's no field {name} in the template"));
+        (&self.template.fields[field].1, &self.values[field])
+    }
+
+    /// The value of a `{name}` or `{name:text}` field.
+    pub fn get<T: FromStr>(&self, name: &str) -> Result<T, ParseError> {
+        let (kind, value) = self.value(name);
+        assert!(
+            !matches!(kind, Kind::List(_)),
+            "field {name} is a list, so it needs reading with `list`"
+        );
+        parse_item(name, value.line, value.items[0])
+    }
+
+    /// The values of a `{name:list}` field.
+    pub fn list<T: FromStr>(&self, name: &str) -> Result<Vec<T>, ParseError> {
+        let (_, value) = self.value(name);
+        value
+            .items
+            .iter()
+            .map(|item| parse_item(name, value.line, *item))
+            .collect()
+    }
+}
+
+fn parse_item<T: FromStr>(
+    name: &str,
+    line: usize,
+    (text, column): (&str, usize),
+) -> Result<T, ParseError> {
+    text.parse().map_err(|_| ParseError {
+        line,
+        column,
+        message: format!("'{text}' isn't a valid {name}"),
+    })
+}
+
+/// Something read from input that matches a template, filling in a field of its own from
+/// each field of the template.
+pub trait FromRecord: Sized {
+    const TEMPLATE: &'static str;
+
+    fn from_record(record: &Record) -> Result<Self, ParseError>;
+}
+
+/// Reads every record in the input.
+pub fn parse_records<T: FromRecord>(text: &str) -> Result<Vec<T>, ParseError> {
+    parse_records_at(text, 1)
+}
+
+/// Reads every record in part of the input, starting at line `first_line`.
+pub fn parse_records_at<T: FromRecord>(
+    text: &str,
+    first_line: usize,
+) -> Result<Vec<T>, ParseError> {
+    Template::new(T::TEMPLATE)
+        .records(text, first_line)?
+        .iter()
+        .map(T::from_record)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MACHINES: &str = "\
+Button A: X+94, Y+34
+Button B: X+22, Y+67
+Prize: X=8400, Y=5400
+
+Button A: X+26, Y+66
+Button B: X+67, Y+21
+Prize: X=12748, Y=12176
+";
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct Machine {
+        a: (i64, i64),
+        b: (i64, i64),
+        prize: (i64, i64),
+    }
+
+    impl FromRecord for Machine {
+        const TEMPLATE: &'static str = "\
+Button A: X+{ax}, Y+{ay}
+Button B: X+{bx}, Y+{by}
+Prize: X={px}, Y={py}";
+
+        fn from_record(record: &Record) -> Result<Self, ParseError> {
+            Ok(Self {
+                a: (record.get("ax")?, record.get("ay")?),
+                b: (record.get("bx")?, record.get("by")?),
+                prize: (record.get("px")?, record.get("py")?),
+            })
+        }
+    }
+
+    fn numbers(template: &str, text: &str, field: &str) -> Vec<Vec<i64>> {
+        Template::new(template)
+            .records(text, 1)
+            .unwrap()
+            .iter()
+            .map(|record| record.list(field).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn machines() {
+        let machines: Vec<Machine> = parse_records(MACHINES).unwrap();
+        assert_eq!(machines.len(), 2);
+        assert_eq!(
+            machines[1],
+            Machine {
+                a: (26, 66),
+                b: (67, 21),
+                prize: (12748, 12176)
+            }
+        );
+    }
+
+    #[test]
+    fn malformed_machines() {
+        let error = parse_records::<Machine>(&MACHINES.replace("Y+21", "Y-21")).unwrap_err();
+        assert_eq!((error.line, error.column), (6, 17));
+        assert_eq!(error.message, "expected 'Y+', found 'Y'");
+
+        let missing = MACHINES.replace("Button B: X+22, Y+67\n", "");
+        let error = parse_records::<Machine>(&missing).unwrap_err();
+        assert_eq!((error.line, error.column), (2, 1));
+        assert_eq!(error.message, "expected 'Button', found 'P'");
+
+        let error = parse_records::<Machine>(&MACHINES[..MACHINES.len() - 24]).unwrap_err();
+        assert_eq!((error.line, error.column), (7, 1));
+    }
+
+    #[test]
+    fn every_input_format() {
+        // day 1
+        let template = Template::new("{left}   {right}");
+        let lists = template.records("3   4\n4  3\n", 1).unwrap();
+        assert_eq!(lists[1].get::<u64>("right"), Ok(3));
+        // days 2 and 11
+        assert_eq!(
+            numbers("{levels:list}", "7 6 4\n1 2\n", "levels"),
+            [vec![7, 6, 4], vec![1, 2]]
+        );
+        // day 5, in two sections
+        let template = Template::new("{before}|{after}");
+        let rules = template.records("47|53\n97|13\n", 1).unwrap();
+        assert_eq!(rules[1].get::<u64>("before"), Ok(97));
+        assert_eq!(
+            numbers("{pages:list,}", "75,47,61\n", "pages"),
+            [vec![75, 47, 61]]
+        );
+        // day 7
+        let template = Template::new("{test}: {values:list}");
+        let equations = template.records("190: 10 19\n", 1).unwrap();
+        assert_eq!(equations[0].get::<u64>("test"), Ok(190));
+        assert_eq!(equations[0].list::<u64>("values"), Ok(vec![10, 19]));
+        // day 14
+        let template = Template::new("p={px},{py} v={vx},{vy}");
+        let robots = template.records("p=0,4 v=3,-3\n", 1).unwrap();
+        assert_eq!(robots[0].get::<i32>("vy"), Ok(-3));
+        // grids, day 3's memory and day 9's disk map
+        let template = Template::new("{row:text}");
+        let rows = template
+            .records("MMMS\nxmul(2,4)\n2333133121414131402\n", 1)
+            .unwrap();
+        assert_eq!(rows[1].get::<String>("row").as_deref(), Ok("xmul(2,4)"));
+    }
+
+    #[test]
+    fn bad_values() {
+        let template = Template::new("{a}|{b}");
+        let records = template.records("1|2\n3|300\n", 10).unwrap();
+        let error = records[1].get::<u8>("b").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 11, column 3: '300' isn't a valid b"
+        );
+
+        let error = Template::new("{test}: {values:list}")
+            .records("190: 10 x\n", 1)
+            .unwrap_err();
+        assert_eq!((error.line, error.column), (1, 9));
+    }
+}
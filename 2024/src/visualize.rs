@@ -0,0 +1,333 @@
+use std::{
+    io::{self, IsTerminal, Stdout, Write},
+    time::{Duration, Instant},
+};
+
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    execute, queue,
+    style::{Print, ResetColor, SetForegroundColor},
+    terminal,
+};
+
+pub use crossterm::style::Color;
+
+use crate::render::Frame;
+
+const MIN_FPS: f64 = 0.25;
+const MAX_FPS: f64 = 1000.0;
+
+/// A grid of coloured characters making up one frame of a terminal visualisation.
+#[derive(Clone, Debug)]
+pub struct Canvas {
+    width: usize,
+    height: usize,
+    cells: Vec<(char, Option<Color>)>,
+}
+
+impl Canvas {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![(' ', None); width * height],
+        }
+    }
+
+    /// Builds a canvas from lines of text, colouring each character with `color_of`.
+    pub fn from_lines<S: AsRef<str>>(
+        lines: &[S],
+        color_of: impl Fn(char) -> Option<Color>,
+    ) -> Self {
+        let width = lines
+            .iter()
+            .map(|l| l.as_ref().chars().count())
+            .max()
+            .unwrap_or(0);
+        let mut canvas = Self::new(width, lines.len());
+        for (y, line) in lines.iter().enumerate() {
+            for (x, c) in line.as_ref().chars().enumerate() {
+                canvas.set(x, y, c, color_of(c));
+            }
+        }
+        canvas
+    }
+
+    /// Draws a density frame the way the puzzle illustrations do: `.` for empty cells and the
+    /// number of entities otherwise (`+` for more than 9).
+    pub fn from_frame(frame: &Frame) -> Self {
+        let mut canvas = Self::new(frame.width() as usize, frame.height() as usize);
+        for y in 0..frame.height() {
+            for x in 0..frame.width() {
+                let (c, color) = match frame.density(x, y) {
+                    0 => ('.', Some(Color::DarkGrey)),
+                    1 => ('1', Some(Color::Green)),
+                    d @ 2..=9 => (char::from_digit(d, 10).unwrap(), Some(Color::Yellow)),
+                    _ => ('+', Some(Color::Red)),
+                };
+                canvas.set(x as usize, y as usize, c, color);
+            }
+        }
+        canvas
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, c: char, color: Option<Color>) {
+        if x < self.width && y < self.height {
+            self.cells[y * self.width + x] = (c, color);
+        }
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[(char, Option<Color>)]> {
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn plain(&self) -> String {
+        self.rows()
+            .map(|row| row.iter().map(|(c, _)| *c).collect::<String>() + "\n")
+            .collect()
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct VisualizeOptions {
+    pub fps: f64,
+    pub paused: bool,
+}
+
+impl VisualizeOptions {
+    /// Removes `--visualize`, `--fps <n>` and `--paused` from `args`.
+    /// Returns `None` when `--visualize` wasn't given.
+    pub fn extract(args: &mut Vec<String>) -> Option<Self> {
+        let mut enabled = false;
+        let mut options = Self {
+            fps: 10.0,
+            paused: false,
+        };
+        let mut remaining = Vec::new();
+        let mut iter = args.drain(..);
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--visualize" => enabled = true,
+                "--paused" => options.paused = true,
+                "--fps" => {
+                    options.fps = iter
+                        .next()
+                        .and_then(|v| v.parse().ok())
+                        .expect("--fps needs a number")
+                }
+                _ => remaining.push(arg),
+            }
+        }
+        drop(iter);
+        *args = remaining;
+        options.fps = options.fps.clamp(MIN_FPS, MAX_FPS);
+        enabled.then_some(options)
+    }
+}
+
+/// Shows canvases one after another in the terminal.
+///
+/// When stdout is a terminal, frames are drawn in place with colours and can be paused
+/// (space), stepped (n or right arrow), sped up or slowed down (+ and -) and quit (q or esc).
+/// Otherwise every frame is printed as plain text, one after the other, without any delay.
+pub struct Visualizer {
+    out: Stdout,
+    interactive: bool,
+    fps: f64,
+    paused: bool,
+    frame: u64,
+}
+
+impl Visualizer {
+    pub fn new(options: VisualizeOptions) -> io::Result<Self> {
+        let mut out = io::stdout();
+        let interactive = out.is_terminal();
+        if interactive {
+            terminal::enable_raw_mode()?;
+            execute!(out, terminal::EnterAlternateScreen, cursor::Hide)?;
+        }
+        Ok(Self {
+            out,
+            interactive,
+            fps: options.fps,
+            paused: options.paused,
+            frame: 0,
+        })
+    }
+
+    /// Shows the next frame. Returns `false` once the user has asked to quit.
+    pub fn show(&mut self, canvas: &Canvas) -> io::Result<bool> {
+        self.frame += 1;
+        if !self.interactive {
+            writeln!(self.out, "frame {}", self.frame)?;
+            write!(self.out, "{}", canvas.plain())?;
+            writeln!(self.out)?;
+            return Ok(true);
+        }
+
+        queue!(self.out, cursor::MoveTo(0, 0))?;
+        for row in canvas.rows() {
+            let mut current_color = None;
+            for (c, color) in row {
+                if *color != current_color {
+                    match color {
+                        Some(color) => queue!(self.out, SetForegroundColor(*color))?,
+                        None => queue!(self.out, ResetColor)?,
+                    }
+                    current_color = *color;
+                }
+                queue!(self.out, Print(c))?;
+            }
+            queue!(
+                self.out,
+                ResetColor,
+                terminal::Clear(terminal::ClearType::UntilNewLine),
+                Print("\r\n")
+            )?;
+        }
+        self.draw_status()?;
+        self.wait()
+    }
+
+    /// Keeps the last frame on screen until a key is pressed.
+    pub fn finish(&mut self) -> io::Result<()> {
+        if !self.interactive {
+            return Ok(());
+        }
+        queue!(
+            self.out,
+            terminal::Clear(terminal::ClearType::CurrentLine),
+            cursor::MoveToColumn(0),
+            Print(format!(
+                "frame {} | done | press any key to exit",
+                self.frame
+            )),
+        )?;
+        self.out.flush()?;
+        loop {
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    fn draw_status(&mut self) -> io::Result<()> {
+        let state = if self.paused { "paused" } else { "running" };
+        queue!(
+            self.out,
+            terminal::Clear(terminal::ClearType::CurrentLine),
+            cursor::MoveToColumn(0),
+            Print(format!(
+                "frame {} | {:.2} fps | {state} | space: pause, n: step, +/-: speed, q: quit",
+                self.frame, self.fps
+            )),
+        )?;
+        self.out.flush()
+    }
+
+    // waits until the next frame is due, handling key presses in the meantime
+    fn wait(&mut self) -> io::Result<bool> {
+        let mut deadline = Instant::now() + Duration::from_secs_f64(1.0 / self.fps);
+        loop {
+            let now = Instant::now();
+            if !self.paused && now >= deadline {
+                return Ok(true);
+            }
+            let timeout = if self.paused {
+                Duration::from_millis(250)
+            } else {
+                deadline - now
+            };
+            if !event::poll(timeout)? {
+                continue;
+            }
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(false),
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    return Ok(false)
+                }
+                KeyCode::Char(' ') => self.paused = !self.paused,
+                KeyCode::Char('n') | KeyCode::Right if self.paused => return Ok(true),
+                KeyCode::Char('+') | KeyCode::Char('=') => {
+                    self.fps = (self.fps * 2.0).min(MAX_FPS);
+                    deadline = Instant::now() + Duration::from_secs_f64(1.0 / self.fps);
+                }
+                KeyCode::Char('-') => {
+                    self.fps = (self.fps / 2.0).max(MIN_FPS);
+                    deadline = Instant::now() + Duration::from_secs_f64(1.0 / self.fps);
+                }
+                _ => {}
+            }
+            self.draw_status()?;
+        }
+    }
+}
+
+impl Drop for Visualizer {
+    fn drop(&mut self) {
+        if self.interactive {
+            let _ = execute!(self.out, cursor::Show, terminal::LeaveAlternateScreen);
+            let _ = terminal::disable_raw_mode();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn extract_takes_out_its_flags() {
+        let mut rest = args(&["--part2", "--visualize", "--fps", "20", "--paused", "--map"]);
+        let options = VisualizeOptions::extract(&mut rest).unwrap();
+        assert_eq!(options.fps, 20.0);
+        assert!(options.paused);
+        assert_eq!(rest, args(&["--part2", "--map"]));
+
+        let mut rest = args(&["--fps", "5000", "--report"]);
+        assert!(VisualizeOptions::extract(&mut rest).is_none());
+        assert_eq!(rest, args(&["--report"]));
+
+        let mut rest = args(&["--visualize", "--fps", "0"]);
+        assert_eq!(VisualizeOptions::extract(&mut rest).unwrap().fps, MIN_FPS);
+    }
+
+    #[test]
+    fn canvas_drawing() {
+        let mut canvas =
+            Canvas::from_lines(&["#.", "...", ""], |c| (c == '#').then_some(Color::Red));
+        assert_eq!(canvas.plain(), "#. \n...\n   \n");
+        assert_eq!(canvas.rows().next().unwrap()[0], ('#', Some(Color::Red)));
+
+        canvas.set(2, 2, '@', Some(Color::Green));
+        // off the canvas, so ignored
+        canvas.set(3, 0, 'x', None);
+        assert_eq!(canvas.plain(), "#. \n...\n  @\n");
+    }
+
+    #[test]
+    fn canvas_from_frame() {
+        let mut points = vec![(0, 0), (1, 0), (1, 0)];
+        points.extend(std::iter::repeat_n((2, 0), 12));
+        let frame = Frame::from_points(4, 1, points);
+        let canvas = Canvas::from_frame(&frame);
+        assert_eq!(canvas.plain(), "12+.\n");
+        assert_eq!(
+            canvas.rows().next().unwrap()[3],
+            ('.', Some(Color::DarkGrey))
+        );
+    }
+}
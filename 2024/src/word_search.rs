@@ -0,0 +1,147 @@
+use crate::{grid::Grid, pattern::Matcher};
+
+pub use crate::pattern::Mask;
+
+/// The eight directions a word can run in: right, left, down, up and the four diagonals.
+pub const DIRECTIONS: [(isize, isize); 8] = [
+    (1, 0),
+    (-1, 0),
+    (0, 1),
+    (0, -1),
+    (1, 1),
+    (-1, -1),
+    (1, -1),
+    (-1, 1),
+];
+
+/// One occurrence of a word in a grid.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WordMatch {
+    pub direction: (isize, isize),
+    /// The position of each letter of the word, from the first to the last.
+    pub cells: Vec<(usize, usize)>,
+}
+
+/// Finds every occurrence of `word` running in any of the eight directions.
+pub fn find_word(grid: &Grid<char>, word: &str) -> Vec<WordMatch> {
+    // a single letter reads the same every way, so only count it once
+    if word.chars().count() == 1 {
+        return find_word_in(grid, word, &DIRECTIONS[..1]);
+    }
+    find_word_in(grid, word, &DIRECTIONS)
+}
+
+/// Finds every occurrence of `word` running in one of the given directions.
+pub fn find_word_in(
+    grid: &Grid<char>,
+    word: &str,
+    directions: &[(isize, isize)],
+) -> Vec<WordMatch> {
+    let letters: Vec<char> = word.chars().collect();
+    let mut matches = Vec::new();
+    if letters.is_empty() {
+        return matches;
+    }
+
+    for start in grid.positions() {
+        if grid[start] != letters[0] {
+            continue;
+        }
+        for direction in directions {
+            let cells: Option<Vec<_>> = (0..letters.len())
+                .map(|i| grid.step(start, *direction, i))
+                .collect();
+            let Some(cells) = cells else {
+                continue;
+            };
+            if cells
+                .iter()
+                .zip(&letters)
+                .all(|(cell, c)| grid[*cell] == *c)
+            {
+                matches.push(WordMatch {
+                    direction: *direction,
+                    cells,
+                });
+            }
+        }
+    }
+    matches
+}
+
+/// The top left corner of every place the mask matches the grid.
+pub fn find_mask(grid: &Grid<char>, mask: &Mask) -> Vec<(usize, usize)> {
+    Matcher::new(grid).find(mask)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+MMMSXXMASM
+MSAMXMSMSA
+AMXSXMAAMM
+MSAMASMSMX
+XMASAMXAMM
+XXAMMXXAMA
+SMSMSASXSS
+SAXAMASAAA
+MAMMMXMMMM
+MXMXAXMASX";
+
+    #[test]
+    fn example() {
+        let grid = Grid::parse(EXAMPLE);
+        assert_eq!(find_word(&grid, "XMAS").len(), 18);
+        let x_mas = Mask::parse(&["M.S", ".A.", "M.S"], '.');
+        assert_eq!(Matcher::new(&grid).find_any_orientation(&x_mas).len(), 9);
+    }
+
+    #[test]
+    fn every_direction() {
+        let grid = Grid::parse(
+            "\
+S..S..S
+.A.A.A.
+..MMM..
+SAMXMAS
+..MMM..
+.A.A.A.
+S..S..S",
+        );
+        let matches = find_word(&grid, "XMAS");
+        assert_eq!(matches.len(), 8);
+        for direction in DIRECTIONS {
+            let m = matches.iter().find(|m| m.direction == direction).unwrap();
+            let (dx, dy) = direction;
+            let expected = (0..4)
+                .map(|i| ((3 + dx * i) as usize, (3 + dy * i) as usize))
+                .collect::<Vec<_>>();
+            assert_eq!(m.cells, expected);
+        }
+        assert_eq!(find_word_in(&grid, "XMAS", &DIRECTIONS[..2]).len(), 2);
+    }
+
+    #[test]
+    fn match_cells() {
+        let grid = Grid::parse("XMASAMX\n.......");
+        assert_eq!(
+            find_word(&grid, "XMAS"),
+            [
+                WordMatch {
+                    direction: (1, 0),
+                    cells: vec![(0, 0), (1, 0), (2, 0), (3, 0)],
+                },
+                WordMatch {
+                    direction: (-1, 0),
+                    cells: vec![(6, 0), (5, 0), (4, 0), (3, 0)],
+                },
+            ]
+        );
+        // single letters are found once each, and nothing is found for an empty word
+        assert_eq!(find_word(&grid, "A").len(), 2);
+        assert_eq!(find_word(&grid, ""), []);
+        assert_eq!(find_mask(&grid, &Mask::parse(&["AM", ".."], '.')), [(4, 0)]);
+    }
+}